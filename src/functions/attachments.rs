@@ -0,0 +1,126 @@
+use std::io::Cursor;
+use std::sync::OnceLock;
+use hmac::{Hmac, Mac};
+use image::ImageReader;
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Maximal size of an attachment in bytes.
+pub const MAX_ATTACHMENT_SIZE: usize = 25 * 1024 * 1024;
+/// Maximal size of an attachment recognized as an image, larger images are refused
+/// so clients never have to render huge previews.
+pub const MAX_IMAGE_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024;
+/// How long a signed download URL stays valid, in seconds.
+pub const DOWNLOAD_URL_SECONDS: i64 = 60 * 60;
+const MAX_FILENAME_LENGTH: usize = 128;
+
+static URL_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+
+// Magic bytes of the recognized file types, checked in order
+const SIGNATURES: [(&[u8], &str); 12] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"OggS", "audio/ogg"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+];
+
+/// Detects the content type from the bytes of the file, the name and type sent by the client are never trusted.
+pub fn sniff_content_type(bytes: &[u8]) -> &'static str {
+    if let Some((_, content_type)) = SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return content_type;
+    }
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" {
+        match &bytes[8..12] {
+            b"WEBP" => return "image/webp",
+            b"WAVE" => return "audio/wav",
+            _ => {}
+        }
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return "video/mp4";
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return "text/plain";
+    }
+    "application/octet-stream"
+}
+
+/// Returns whether the content type is an image the server can read the dimensions of
+pub fn is_image(content_type: &str) -> bool {
+    matches!(content_type, "image/png" | "image/jpeg" | "image/gif" | "image/webp")
+}
+
+/// Reads the width and height of an image from its header, without decoding the pixels
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(bytes)).with_guessed_format().ok()?.into_dimensions().ok()
+}
+
+/// Keeps the filename safe to use in a storage key and a `Content-Disposition` header
+pub fn sanitize_filename(filename: &str) -> Result<String, String> {
+    // Clients may send a full path, only its last part is kept
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.');
+    if sanitized.is_empty() {
+        return Err(String::from("Filename is empty"));
+    }
+    if sanitized.chars().count() > MAX_FILENAME_LENGTH {
+        return Err(format!("Filename is too long (>{} characters)", MAX_FILENAME_LENGTH));
+    }
+    Ok(sanitized.to_string())
+}
+
+/// Signs a download URL of an attachment valid until `expires` (unix seconds)
+pub fn sign_download(attachment_id: Uuid, expires: i64) -> String {
+    let signature = download_mac(attachment_id, expires).finalize().into_bytes();
+    signature.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Checks the signature of a download URL, the expiry is checked by the caller
+pub fn verify_download(attachment_id: Uuid, expires: i64, signature: &str) -> bool {
+    // An odd length leaves a last slice out of bounds, which rejects the signature
+    let bytes: Option<Vec<u8>> = (0..signature.len()).step_by(2)
+        .map(|i| signature.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect();
+    match bytes {
+        // Constant time comparison
+        Some(bytes) => download_mac(attachment_id, expires).verify_slice(&bytes).is_ok(),
+        None => false,
+    }
+}
+
+/// Loads the key signing download URLs from `ATTACHMENT_URL_SECRET`, called once at startup.
+///
+/// Without it a random key is used: signed URLs then stop working when the server restarts
+/// and are not accepted by other instances.
+///
+/// # Returns
+/// Whether the key was configured.
+pub fn init_url_secret() -> bool {
+    let configured = std::env::var("ATTACHMENT_URL_SECRET").ok().filter(|secret| !secret.is_empty());
+    let is_configured = configured.is_some();
+    URL_SECRET.get_or_init(|| configured.map(String::into_bytes).unwrap_or_else(random_secret));
+    is_configured
+}
+
+fn random_secret() -> Vec<u8> {
+    [Uuid::new_v4().into_bytes(), Uuid::new_v4().into_bytes()].concat()
+}
+
+fn download_mac(attachment_id: Uuid, expires: i64) -> Hmac<Sha256> {
+    let secret = URL_SECRET.get_or_init(random_secret);
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(attachment_id.as_bytes());
+    mac.update(&expires.to_be_bytes());
+    mac
+}
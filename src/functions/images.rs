@@ -0,0 +1,106 @@
+use std::io::Cursor;
+use image::imageops::FilterType;
+use image::{ImageFormat, ImageReader, Limits};
+use sha2::{Digest, Sha256};
+
+/// Maximal size of an uploaded image in bytes.
+pub const MAX_UPLOAD_SIZE: usize = 8 * 1024 * 1024;
+const MAX_INPUT_DIMENSION: u32 = 4096;
+const ALLOWED_FORMATS: [ImageFormat; 4] = [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Gif, ImageFormat::WebP];
+
+/// Kind of profile image, decides the stored sizes and aspect ratio.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageKind {
+    Avatar,
+    Banner,
+}
+
+impl ImageKind {
+    pub fn parse(value: &str) -> Option<ImageKind> {
+        match value {
+            "avatars" => Some(ImageKind::Avatar),
+            "banners" => Some(ImageKind::Banner),
+            _ => None,
+        }
+    }
+
+    /// Storage directory and URL segment of the kind
+    pub fn dir(&self) -> &'static str {
+        match self {
+            ImageKind::Avatar => "avatars",
+            ImageKind::Banner => "banners",
+        }
+    }
+
+    /// Column of `joltamp.users` holding the hash of the image
+    pub fn column(&self) -> &'static str {
+        match self {
+            ImageKind::Avatar => "avatar",
+            ImageKind::Banner => "banner",
+        }
+    }
+
+    /// Stored widths, largest first
+    pub fn sizes(&self) -> &'static [u32] {
+        match self {
+            ImageKind::Avatar => &[512, 256, 128, 64],
+            ImageKind::Banner => &[1200, 600, 300],
+        }
+    }
+
+    // Avatars are square, banners 3:1
+    fn dimensions(&self, width: u32) -> (u32, u32) {
+        match self {
+            ImageKind::Avatar => (width, width),
+            ImageKind::Banner => (width, width / 3),
+        }
+    }
+
+    /// Storage key of one size of an image
+    pub fn key(&self, hash: &str, size: u32) -> String {
+        format!("{}/{}/{}.png", self.dir(), hash, size)
+    }
+}
+
+/// Image re-encoded into every size of its kind.
+pub struct ProcessedImage {
+    /// SHA-256 of the largest variant, used as content address
+    pub hash: String,
+    pub variants: Vec<(u32, Vec<u8>)>,
+}
+
+/// Sniffs, decodes and re-encodes an uploaded image into every size of its kind.
+///
+/// The format is detected from the content, not from the declared type. Re-encoding to PNG
+/// drops metadata (EXIF location etc.) and anything hidden after the image data.
+///
+/// # Returns
+/// The processed image, or a message describing why the upload was rejected.
+pub fn process_image(kind: ImageKind, bytes: &[u8]) -> Result<ProcessedImage, String> {
+    if bytes.len() > MAX_UPLOAD_SIZE {
+        return Err(format!("Image is too large (>{} bytes)", MAX_UPLOAD_SIZE));
+    }
+    let format = image::guess_format(bytes).map_err(|_| String::from("Unknown image format"))?;
+    if !ALLOWED_FORMATS.contains(&format) {
+        return Err(String::from("Only PNG, JPEG, GIF and WebP images are allowed"));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_INPUT_DIMENSION);
+    limits.max_image_height = Some(MAX_INPUT_DIMENSION);
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|_| format!("Image cannot be decoded or is larger than {0}x{0}", MAX_INPUT_DIMENSION))?;
+
+    let mut variants = Vec::new();
+    for size in kind.sizes() {
+        let (width, height) = kind.dimensions(*size);
+        let mut encoded = Vec::new();
+        image.resize_to_fill(width, height, FilterType::Lanczos3)
+            .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+            .map_err(|_| String::from("Image cannot be encoded"))?;
+        variants.push((*size, encoded));
+    }
+    let hash = Sha256::digest(&variants[0].1).iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(ProcessedImage { hash, variants })
+}
@@ -0,0 +1,26 @@
+use anyhow::{Error, Result};
+use scylla::frame::response::result::{CqlValue, Row};
+use scylla::QueryResult;
+
+/// Returns whether a lightweight transaction (`IF ...`) was applied.
+///
+/// Lightweight transactions answer with an `[applied]` column first.
+pub fn applied(res: QueryResult) -> Result<bool> {
+    let row = res.into_rows_result()?.first_row::<Row>()?;
+    match row.columns.first() {
+        Some(Some(CqlValue::Boolean(applied))) => Ok(*applied),
+        _ => Err(Error::msg("Unexpected lightweight transaction result")),
+    }
+}
+
+/// Fails unless a write guarded by `IF EXISTS` found its row.
+///
+/// Rows of `joltamp.users` are moved to a new key on a username change, a plain `UPDATE`
+/// on the old key would create a partial row instead of failing.
+pub fn ensure_applied(res: QueryResult) -> Result<()> {
+    if applied(res)? {
+        Ok(())
+    } else {
+        Err(Error::msg("Row no longer exists"))
+    }
+}
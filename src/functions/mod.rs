@@ -1 +1,4 @@
-//pub mod date;
\ No newline at end of file
+pub mod validation;
+pub mod images;
+pub mod attachments;
+pub mod lwt;
\ No newline at end of file
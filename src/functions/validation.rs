@@ -0,0 +1,157 @@
+use unicode_normalization::UnicodeNormalization;
+use url::Url;
+
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_DISPLAYNAME_LENGTH: usize = 32;
+pub const MAX_DESC_LENGTH: usize = 300;
+pub const MAX_PRONOUNS_LENGTH: usize = 40;
+pub const MAX_LINKS: usize = 5;
+pub const MAX_LINK_LENGTH: usize = 200;
+pub const MAX_NOTE_LENGTH: usize = 256;
+
+/// Normalizes and validates a display name.
+///
+/// The name is NFKC normalized, control and invisible characters are stripped and
+/// surrounding whitespace is trimmed. Names mixing Latin with Cyrillic or Greek letters
+/// are rejected, since they are the usual way to impersonate someone (`pаypal` with a Cyrillic `а`).
+///
+/// # Returns
+/// The normalized display name, or a message describing why it was rejected.
+pub fn normalize_displayname(value: &str) -> Result<String, String> {
+    let name: String = value.nfkc().filter(|c| !is_invisible(*c) && !c.is_control()).collect();
+    let name = name.trim().to_string();
+    let length = name.chars().count();
+
+    if length == 0 {
+        return Err(String::from("Display name cannot be empty"));
+    }
+    if length > MAX_DISPLAYNAME_LENGTH {
+        return Err(format!("Display name is too long (>{})", MAX_DISPLAYNAME_LENGTH));
+    }
+    if has_confusable_scripts(&name) {
+        return Err(String::from("Display name mixes confusable scripts"));
+    }
+    Ok(name)
+}
+
+/// Normalizes and validates a new username.
+///
+/// Usernames double as the initial display name at registration, so they follow the
+/// display name rules (normalization, length, confusable scripts) and need at least 3 characters.
+pub fn normalize_username(value: &str) -> Result<String, String> {
+    let name = normalize_displayname(value).map_err(|err| err.replacen("Display name", "Username", 1))?;
+    if name.chars().count() < MIN_USERNAME_LENGTH {
+        return Err(format!("Username is too short (<{})", MIN_USERNAME_LENGTH));
+    }
+    Ok(name)
+}
+
+/// Normalizes and validates a profile description.
+///
+/// The text is NFC normalized, invisible characters and control characters other than
+/// line breaks are stripped, and the length is capped.
+pub fn normalize_desc(value: &str) -> Result<String, String> {
+    let desc: String = value.nfc().filter(|c| !is_invisible(*c) && (*c == '\n' || !c.is_control())).collect();
+    let desc = desc.trim().to_string();
+    if desc.chars().count() > MAX_DESC_LENGTH {
+        return Err(format!("Description is too long (>{})", MAX_DESC_LENGTH));
+    }
+    Ok(desc)
+}
+
+/// Normalizes and validates pronouns, a short single line text.
+pub fn normalize_pronouns(value: &str) -> Result<String, String> {
+    let pronouns: String = value.nfc().filter(|c| !is_invisible(*c) && !c.is_control()).collect();
+    let pronouns = pronouns.trim().to_string();
+    if pronouns.chars().count() > MAX_PRONOUNS_LENGTH {
+        return Err(format!("Pronouns are too long (>{})", MAX_PRONOUNS_LENGTH));
+    }
+    Ok(pronouns)
+}
+
+/// Normalizes and validates a private note about another user, line breaks are kept.
+pub fn normalize_note(value: &str) -> Result<String, String> {
+    let note: String = value.nfc().filter(|c| !is_invisible(*c) && (*c == '\n' || !c.is_control())).collect();
+    let note = note.trim().to_string();
+    if note.chars().count() > MAX_NOTE_LENGTH {
+        return Err(format!("Note is too long (>{})", MAX_NOTE_LENGTH));
+    }
+    Ok(note)
+}
+
+/// Validates profile links.
+///
+/// Only absolute `http`/`https` URLs with a host are allowed, credentials in the URL are
+/// rejected so links cannot be used to phish with `https://trusted.com@evil.com` style URLs.
+///
+/// # Returns
+/// The links in their normalized form, or a message naming the first invalid link.
+pub fn validate_links(links: &[String]) -> Result<Vec<String>, String> {
+    if links.len() > MAX_LINKS {
+        return Err(format!("Too many links (>{})", MAX_LINKS));
+    }
+    let mut valid = Vec::new();
+    for link in links {
+        let link = link.trim();
+        if link.len() > MAX_LINK_LENGTH {
+            return Err(format!("Link is too long (>{})", MAX_LINK_LENGTH));
+        }
+        let url = Url::parse(link).map_err(|_| format!("Invalid link: {}", link))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none_or(str::is_empty) {
+            return Err(format!("Only http(s) links are allowed: {}", link));
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err(format!("Links cannot contain credentials: {}", link));
+        }
+        valid.push(url.to_string());
+    }
+    Ok(valid)
+}
+
+/// Folds text for search matching: NFKD normalized, diacritics removed and lowercased,
+/// so `Zoë` and `ZOE` compare equal.
+pub fn search_key(value: &str) -> String {
+    value.nfkd().filter(|c| !is_combining_mark(*c) && !is_invisible(*c)).flat_map(char::to_lowercase).collect()
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}')
+}
+
+// Zero-width, bidi override and other invisible formatting characters
+fn is_invisible(c: char) -> bool {
+    matches!(c,
+        '\u{00AD}' | '\u{034F}' | '\u{061C}' | '\u{115F}' | '\u{1160}' | '\u{180E}' |
+        '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{206F}' |
+        '\u{3164}' | '\u{FE00}'..='\u{FE0F}' | '\u{FEFF}' | '\u{FFA0}'
+    )
+}
+
+#[derive(PartialEq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+fn script_of(c: char) -> Option<Script> {
+    match c {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' => Some(Script::Latin),
+        '\u{0370}'..='\u{03FF}' => Some(Script::Greek),
+        '\u{0400}'..='\u{04FF}' => Some(Script::Cyrillic),
+        _ => None,
+    }
+}
+
+// Latin, Greek and Cyrillic share many look-alike letters, a single name should use only one of them
+fn has_confusable_scripts(name: &str) -> bool {
+    let mut found: Option<Script> = None;
+    for script in name.chars().filter_map(script_of) {
+        match &found {
+            Some(first) if *first != script => return true,
+            Some(_) => {}
+            None => found = Some(script),
+        }
+    }
+    false
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use scylla::Session;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use crate::types::user::User;
+
+// Events buffered per user before a slow connection starts missing them
+const EVENT_BUFFER: usize = 256;
+
+/// Event pushed to every gateway connection of a user.
+///
+/// Sent to clients as `{"t": "<type>", "d": <data>}`.
+#[derive(Clone, Serialize)]
+pub struct GatewayEvent {
+    pub t: &'static str,
+    pub d: Value,
+}
+
+/// In-process fan-out of events to the connected clients.
+///
+/// Every user with an open connection has its own channel, so a connection only receives the
+/// events of its user. Connections only exist on the instance they were opened on, so events
+/// do not cross instances.
+#[derive(Clone, Default)]
+pub struct Gateway {
+    senders: Arc<Mutex<HashMap<Uuid, broadcast::Sender<GatewayEvent>>>>,
+}
+
+impl Gateway {
+
+    /// Publishes an event to every connection of the user, nothing happens if none is open
+    pub fn publish(&self, user_id: Uuid, t: &'static str, d: impl Serialize) {
+        let Ok(d) = serde_json::to_value(d) else { return };
+        let mut senders = self.senders.lock().unwrap();
+        if let Some(sender) = senders.get(&user_id) {
+            // Sending only fails once every connection of the user is gone
+            if sender.send(GatewayEvent { t, d }).is_err() {
+                senders.remove(&user_id);
+            }
+        }
+    }
+
+    /// Opens a channel of the user for a new connection
+    pub fn subscribe(&self, user_id: Uuid) -> broadcast::Receiver<GatewayEvent> {
+        let mut senders = self.senders.lock().unwrap();
+        senders.entry(user_id).or_insert_with(|| broadcast::channel(EVENT_BUFFER).0).subscribe()
+    }
+
+    /// Drops the channel of the user once its last connection is closed
+    pub fn unsubscribe(&self, user_id: Uuid, receiver: broadcast::Receiver<GatewayEvent>) {
+        drop(receiver);
+        let mut senders = self.senders.lock().unwrap();
+        if senders.get(&user_id).is_some_and(|sender| sender.receiver_count() == 0) {
+            senders.remove(&user_id);
+        }
+    }
+
+    /// Closes every connection of the user, used when its sessions are revoked or the account is
+    /// suspended or deleted. Clients still holding a valid credential can connect again.
+    pub fn disconnect(&self, user_id: Uuid) {
+        self.senders.lock().unwrap().remove(&user_id);
+    }
+
+    /// Closes every connection of the user and of the bots owned by the user
+    pub async fn disconnect_with_bots(&self, session: &Arc<Session>, user_id: Uuid) {
+        self.disconnect(user_id);
+        match User::owned_bots(session, user_id).await {
+            Ok(bots) => bots.into_iter().for_each(|bot_id| self.disconnect(bot_id)),
+            Err(err) => tracing::warn!("Cannot disconnect bots of {}: {:?}", user_id, err),
+        }
+    }
+}
@@ -2,15 +2,23 @@ mod routes;
 mod security;
 mod types;
 mod functions;
+mod storage;
+mod gateway;
+mod notifier;
 
 use std::error::Error;
 use std::sync::Arc;
+use std::time::Duration;
+use axum::extract::DefaultBodyLimit;
 use axum::http::StatusCode;
-use axum::Router;
-use axum::routing::{get, post};
+use axum::{Extension, Router};
+use axum::routing::{get, patch, post, put};
 use scylla::{ExecutionProfile, Session, SessionBuilder};
 use scylla::statement::Consistency;
 use crate::routes::friends::getfriends::get_friends;
+use crate::routes::friends::nickname::set_nickname;
+use crate::routes::friends::suggestions::get_suggestions;
+use crate::routes::users::note::set_note;
 use crate::routes::users::changeselfinfo::change_selfinfo;
 use crate::routes::users::getinfo::get_info;
 use crate::routes::users::getselfinfo::get_self_info;
@@ -18,13 +26,57 @@ use crate::routes::users::isadmin::is_admin;
 use crate::routes::users::register::register;
 use crate::routes::users::login::login;
 use crate::routes::users::setstatus::set_status;
+use crate::routes::users::reauth::reauth;
+use crate::routes::tokens::createtoken::create_token;
+use crate::routes::tokens::listtokens::list_tokens;
+use crate::routes::tokens::revoketoken::revoke_token;
+use crate::routes::bots::createbot::create_bot;
+use crate::routes::users::resetpassword::reset_password;
+use crate::routes::users::patchself::patch_self;
+use crate::routes::users::changeusername::change_username;
+use crate::routes::users::search::search;
+use crate::routes::users::lookup::lookup_users;
+use crate::routes::users::deleteself::delete_self;
+use crate::routes::users::privacy::update_privacy;
+use crate::routes::users::settings::{get_settings, put_settings};
+use crate::routes::gateway::connect::connect;
+use crate::gateway::Gateway;
+use crate::routes::users::export::{download_export, get_export, start_export};
+use crate::types::deletion::AccountDeletion;
+use crate::types::export::ExportJob;
+use crate::types::migration::Migration;
+use crate::routes::admin::searchusers::search_users;
+use crate::routes::admin::getuser::get_user;
+use crate::routes::admin::forcepasswordreset::force_password_reset;
+use crate::routes::admin::revokesessions::revoke_sessions;
+use crate::routes::admin::badges::{grant_badge, revoke_badge};
+use crate::routes::admin::setadmin::set_admin;
+use crate::routes::admin::auditlog::audit_log;
+use crate::routes::admin::suspend::{suspend, unsuspend};
+use crate::routes::admin::managebadges::{create_badge, edit_badge, retire_badge};
+use crate::routes::badges::getbadges::get_badges;
+use crate::routes::badges::lookupbadges::lookup_badges;
+use crate::routes::media::uploadimage::{upload_avatar, upload_banner};
+use crate::routes::media::getmedia::get_media;
+use crate::routes::attachments::createattachment::create_attachment;
+use crate::routes::attachments::uploadattachment::upload_attachment;
+use crate::routes::attachments::downloadurl::get_download_url;
+use crate::routes::attachments::download::download_attachment;
+use crate::functions::images::MAX_UPLOAD_SIZE;
+use crate::functions::attachments::{init_url_secret, MAX_ATTACHMENT_SIZE};
+use crate::storage::Storage;
+use crate::storage::local::LocalStorage;
+use crate::notifier::Notifier;
+use crate::notifier::log::LogNotifier;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    tracing_subscriber::fmt::init();
+
     // SETUP SCYLLA DB
     let uri = std::env::var("SCYLLA_URI")
         .unwrap_or_else(|_| "172.17.0.2:9042".to_string());
-    println!("Trying to connect to ScyllaDB via uri: {}",uri);
+    tracing::info!("Trying to connect to ScyllaDB via uri: {}",uri);
     let handle = ExecutionProfile::builder()
         .consistency(Consistency::One)
         .build()
@@ -35,12 +87,51 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .default_execution_profile_handle(handle)
         .build()
         .await?;
-    println!("Connected to ScyllaDB");
+    tracing::info!("Connected to ScyllaDB");
     let session = Arc::new(session);
 
-    // SETUP AXUM
+    // SETUP STORAGE
+    let storage_path = std::env::var("STORAGE_PATH")
+        .unwrap_or_else(|_| "./storage".to_string());
+    tracing::info!("Storing media in: {}",storage_path);
+    let storage: Arc<dyn Storage> = Arc::new(LocalStorage::new(storage_path));
 
-    tracing_subscriber::fmt::init();
+    // SETUP ATTACHMENT DOWNLOAD URLS
+    if !init_url_secret() {
+        tracing::warn!("ATTACHMENT_URL_SECRET is not set, signing attachment URLs with a random key. \
+                       They will stop working on restart and are not valid on other instances.");
+    }
+
+    // SETUP NOTIFICATIONS
+    let notifier: Arc<dyn Notifier> = Arc::new(LogNotifier);
+
+    // SETUP GATEWAY
+    let gateway = Gateway::default();
+
+    // ONE-OFF DATA MIGRATIONS
+    let migration_session = session.clone();
+    tokio::spawn(async move {
+        if let Err(err) = Migration::run_pending(&migration_session).await {
+            tracing::error!("Migration failed: {:?}", err);
+        }
+    });
+
+    // PERIODIC CLEANUP: PURGE DELETED ACCOUNTS AND EXPIRED EXPORTS
+    let (cleanup_session, cleanup_storage) = (session.clone(), storage.clone());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            if let Err(err) = AccountDeletion::purge_due(&cleanup_session, &cleanup_storage).await {
+                tracing::error!("Account purge failed: {:?}", err);
+            }
+            if let Err(err) = ExportJob::remove_expired(&cleanup_session, &cleanup_storage).await {
+                tracing::error!("Export cleanup failed: {:?}", err);
+            }
+        }
+    });
+
+    // SETUP AXUM
     let app = Router::new()
         .route("/api/v0/", get(|| async {(StatusCode::OK, "All services running!")}))
         .route("/api/v0/users/isAdmin/{id}", get(is_admin))
@@ -50,7 +141,52 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .route("/api/v0/users/getSelfInfo", post(get_self_info))
         .route("/api/v0/users/setStatus", post(set_status))
         .route("/api/v0/users/changeSelfInfo", post(change_selfinfo))
+        .route("/api/v0/users/reauth", post(reauth))
+        .route("/api/v0/users/resetPassword", post(reset_password))
+        .route("/api/v0/users/@me", patch(patch_self))
+        .route("/api/v0/users/changeUsername", post(change_username))
+        .route("/api/v0/users/search", post(search))
+        .route("/api/v0/users/lookup", post(lookup_users))
+        .route("/api/v0/users/@me/delete", post(delete_self))
+        .route("/api/v0/users/@me/privacy", patch(update_privacy))
+        .route("/api/v0/users/@me/settings", get(get_settings).put(put_settings))
+        .route("/api/v0/gateway", get(connect))
+        .route("/api/v0/users/@me/export", post(start_export))
+        .route("/api/v0/users/@me/export/{id}", post(get_export))
+        .route("/api/v0/exports/{token}", get(download_export))
+        .route("/api/v0/users/@me/avatar", post(upload_avatar).layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE + 64 * 1024)))
+        .route("/api/v0/users/@me/banner", post(upload_banner).layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE + 64 * 1024)))
+        .route("/api/v0/media/{kind}/{hash}", get(get_media))
+        .route("/api/v0/attachments/create", post(create_attachment))
+        .route("/api/v0/attachments/{id}/upload", put(upload_attachment).layer(DefaultBodyLimit::max(MAX_ATTACHMENT_SIZE)))
+        .route("/api/v0/attachments/{id}/url", post(get_download_url))
+        .route("/api/v0/attachments/{id}/file/{filename}", get(download_attachment))
+        .route("/api/v0/users/{id}/note", post(set_note))
         .route("/api/v0/friends/", post(get_friends))
+        .route("/api/v0/friends/suggestions", post(get_suggestions))
+        .route("/api/v0/friends/{id}/nickname", post(set_nickname))
+        .route("/api/v0/tokens/", post(list_tokens))
+        .route("/api/v0/tokens/create", post(create_token))
+        .route("/api/v0/tokens/revoke", post(revoke_token))
+        .route("/api/v0/bots/create", post(create_bot))
+        .route("/api/v0/admin/searchUsers", post(search_users))
+        .route("/api/v0/admin/users/{id}", post(get_user))
+        .route("/api/v0/admin/users/{id}/forcePasswordReset", post(force_password_reset))
+        .route("/api/v0/admin/users/{id}/revokeSessions", post(revoke_sessions))
+        .route("/api/v0/admin/users/{id}/grantBadge", post(grant_badge))
+        .route("/api/v0/admin/users/{id}/revokeBadge", post(revoke_badge))
+        .route("/api/v0/admin/users/{id}/setAdmin", post(set_admin))
+        .route("/api/v0/admin/users/{id}/suspend", post(suspend))
+        .route("/api/v0/admin/users/{id}/unsuspend", post(unsuspend))
+        .route("/api/v0/admin/auditLog", post(audit_log))
+        .route("/api/v0/admin/badges/create", post(create_badge))
+        .route("/api/v0/admin/badges/{id}/edit", post(edit_badge))
+        .route("/api/v0/admin/badges/{id}/retire", post(retire_badge))
+        .route("/api/v0/badges/", get(get_badges))
+        .route("/api/v0/badges/lookup", post(lookup_badges))
+        .layer(Extension(storage))
+        .layer(Extension(gateway))
+        .layer(Extension(notifier))
         .with_state(session);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::notifier::{Notification, Notifier};
+
+/// Notifier used while no mailer is configured: nothing is delivered, only the kind of
+/// notification is logged. Addresses and tokens are never written to the log.
+// TODO: add an SMTP backed notifier and select it through the environment
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, _email: &str, notification: Notification) -> Result<()> {
+        match notification {
+            Notification::SecurityEvent { kind } => tracing::info!("Security notification not delivered (no mailer): {}", kind),
+            Notification::PasswordReset { .. } => tracing::info!("Password reset notification not delivered (no mailer)"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+pub mod log;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Message sent to the owner of an account, outside of the API.
+pub enum Notification {
+    /// A security relevant change of the account, e.g. `password_changed`
+    SecurityEvent { kind: String },
+    /// Token to choose a new password after an administrator forced a reset
+    // Only read by mail backends, the log notifier never prints it
+    #[allow(dead_code)]
+    PasswordReset { token: String },
+}
+
+/// Delivery of notifications to the e-mail address of an account.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, email: &str, notification: Notification) -> Result<()>;
+}
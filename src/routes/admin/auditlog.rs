@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{NaiveDate, Utc};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestQuery {
+    day: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnEntries(Vec<AuditEntry>),
+    Error(RequestError),
+}
+
+/// Lists admin actions recorded on the given day (`?day=YYYY-MM-DD`, today by default).
+pub async fn audit_log(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Query(query): Query<RequestQuery>,
+) -> (StatusCode, Json<ReturnType>) {
+    if let Err((status, err)) = require_admin(&session, &headers).await {
+        return (status, Json(ReturnType::Error(err)));
+    }
+    let day = query.day.unwrap_or(Utc::now().date_naive());
+
+    match AuditEntry::list_day(&session, day).await {
+        Ok(entries) => (StatusCode::OK, Json(ReturnType::ReturnEntries(entries))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("auditLog#0x01 Internal server error")))),
+    }
+}
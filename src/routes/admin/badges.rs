@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::badge::Badge;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Deserialize)]
+pub struct RequestBadge {
+    #[serde(rename = "badgeId")]
+    badge_id: Uuid,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnBadges(Vec<Uuid>),
+    Error(RequestError),
+}
+
+/// Grants a badge to the user and returns their badges.
+pub async fn grant_badge(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<RequestBadge>,
+) -> (StatusCode, Json<ReturnType>) {
+    change_badge(&session, &headers, user_id, payload.badge_id, true).await
+}
+
+/// Revokes a badge from the user and returns their badges.
+pub async fn revoke_badge(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<RequestBadge>,
+) -> (StatusCode, Json<ReturnType>) {
+    change_badge(&session, &headers, user_id, payload.badge_id, false).await
+}
+
+async fn change_badge(session: &Arc<Session>, headers: &HeaderMap, user_id: Uuid, badge_id: Uuid, grant: bool) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(session, headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Ok(user) = User::from_user_id(user_id).fill_info(session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+
+    // Only badges from the catalog that are not retired can be granted
+    if grant && !matches!(Badge::from_badge_id(session, badge_id).await, Ok(Badge { retired: Some(false) | None, .. })) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Badge does not exist or is retired"))));
+    }
+    let res = if grant {
+        user.grant_badge(session, badge_id).await
+    } else {
+        user.revoke_badge(session, badge_id).await
+    };
+    match res {
+        Ok(user) => {
+            let action = if grant { "grant_badge" } else { "revoke_badge" };
+            AuditEntry::record_logged(session, admin.user_id.unwrap_or(Uuid::nil()), action, Some(user_id), Some(badge_id.to_string())).await;
+            (StatusCode::OK, Json(ReturnType::ReturnBadges(user.badges.unwrap_or_default())))
+        }
+        Err(err) => (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string())))),
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::gateway::Gateway;
+use crate::notifier::{Notification, Notifier};
+use crate::security::auth::require_admin;
+use crate::security::reset::force_password_reset as reset_password;
+use crate::types::audit::AuditEntry;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    Ok,
+    Error(RequestError),
+}
+
+/// Forces a password reset of the user and revokes all of their sessions.
+///
+/// The reset token is sent to the e-mail address of the user, never to the admin, so the
+/// admin cannot set a password on their behalf. The user sets a new password with
+/// `/api/v0/users/resetPassword`.
+pub async fn force_password_reset(
+    State(session): State<Arc<Session>>,
+    Extension(notifier): Extension<Arc<dyn Notifier>>,
+    Extension(gateway): Extension<Gateway>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Ok(user) = User::from_user_id(user_id).fill_info(&session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+
+    let email = user.email.clone().unwrap_or_default();
+    let token = match reset_password(&session, user).await {
+        Ok(token) => token,
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("forcePasswordReset#0x01 Internal server error")))),
+    };
+    gateway.disconnect(user_id);
+    AuditEntry::record_logged(&session, admin.user_id.unwrap_or(Uuid::nil()), "force_password_reset", Some(user_id), None).await;
+    match notifier.notify(&email, Notification::PasswordReset { token: token.to_string() }).await {
+        Ok(()) => (StatusCode::OK, Json(ReturnType::Ok)),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("forcePasswordReset#0x02 Internal server error")))),
+    }
+}
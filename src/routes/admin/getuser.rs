@@ -0,0 +1,93 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::NaiveDate;
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::token::ApiToken;
+use crate::types::color::HexColor;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+use crate::types::username::UsernameChange;
+
+#[derive(Serialize)]
+pub struct AccountDetails {
+    createdat: String,
+    user_id: Option<Uuid>,
+    username: Option<String>,
+    displayname: Option<String>,
+    email: Option<String>,
+    badges: Option<Vec<Uuid>>,
+    status: Option<i8>,
+    bannercolor: Option<HexColor>,
+    backgroundcolor: Option<HexColor>,
+    desc: Option<String>,
+    pronouns: Option<String>,
+    links: Option<Vec<String>>,
+    avatar: Option<String>,
+    banner: Option<String>,
+    isadmin: Option<bool>,
+    isbot: Option<bool>,
+    owner_id: Option<Uuid>,
+    friends: Vec<Uuid>,
+    tokens: Vec<ApiToken>,
+    username_history: Vec<UsernameChange>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnData(Box<AccountDetails>),
+    Error(RequestError),
+}
+
+/// Returns the full account details of a user, except for credentials.
+pub async fn get_user(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Ok(user) = User::from_user_id(user_id).fill_info(&session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+    // Account details are only shown once the access is on record
+    if AuditEntry::record(&session, admin.user_id.unwrap_or(Uuid::nil()), "view_user", Some(user_id), None).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("getUser#0x02 Internal server error"))));
+    }
+    let Ok(user) = user.fetch_friends(&session).await else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("getUser#0x01 Internal server error"))));
+    };
+    let tokens = ApiToken::list_for_user(&session, user_id).await.unwrap_or_default();
+    let username_history = UsernameChange::list_for_user(&session, user_id).await.unwrap_or_default();
+
+    (StatusCode::OK, Json(ReturnType::ReturnData(Box::new(AccountDetails {
+        createdat: user.createdat.unwrap_or(NaiveDate::MIN).format("%Y-%m-%d").to_string(),
+        user_id: user.user_id,
+        username: user.username,
+        displayname: user.displayname,
+        email: user.email,
+        badges: user.badges,
+        status: user.status,
+        bannercolor: user.bannercolor,
+        backgroundcolor: user.backgroundcolor,
+        desc: user.desc,
+        pronouns: user.pronouns,
+        links: user.links,
+        avatar: user.avatar,
+        banner: user.banner,
+        isadmin: user.isadmin,
+        isbot: user.isbot,
+        owner_id: user.owner_id,
+        friends: user.friends.map(|friends| friends.into_keys().collect()).unwrap_or_default(),
+        tokens,
+        username_history,
+    }))))
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::badge::Badge;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestBadge {
+    name: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    priority: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnBadge(Badge),
+    Error(RequestError),
+}
+
+/// Adds a new badge to the catalog, `name` is required.
+pub async fn create_badge(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestBadge>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Some(name) = payload.name.filter(|name| !name.trim().is_empty()) else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Not every field satisfied"))));
+    };
+
+    match Badge::create(&session, name, payload.description, payload.icon, payload.priority.unwrap_or(0)).await {
+        Ok(badge) => {
+            AuditEntry::record_logged(&session, admin.user_id.unwrap_or(Uuid::nil()), "create_badge", None, Some(badge.badge_id.to_string())).await;
+            (StatusCode::CREATED, Json(ReturnType::ReturnBadge(badge)))
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("createBadge#0x01 Internal server error")))),
+    }
+}
+
+/// Edits the fields of a badge that are present in the request.
+pub async fn edit_badge(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(badge_id): Path<Uuid>,
+    Json(payload): Json<RequestBadge>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Ok(mut badge) = Badge::from_badge_id(&session, badge_id).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect badgeId"))));
+    };
+    if let Some(name) = payload.name {
+        if name.trim().is_empty() {
+            return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Badge name cannot be empty"))));
+        }
+        badge.name = name;
+    }
+    if payload.description.is_some() {
+        badge.description = payload.description;
+    }
+    if payload.icon.is_some() {
+        badge.icon = payload.icon;
+    }
+    if let Some(priority) = payload.priority {
+        badge.priority = priority;
+    }
+
+    save_badge(&session, admin.user_id.unwrap_or(Uuid::nil()), badge, "edit_badge").await
+}
+
+/// Retires a badge, it can no longer be granted but users keep it.
+pub async fn retire_badge(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(badge_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Ok(mut badge) = Badge::from_badge_id(&session, badge_id).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect badgeId"))));
+    };
+    badge.retired = Some(true);
+
+    save_badge(&session, admin.user_id.unwrap_or(Uuid::nil()), badge, "retire_badge").await
+}
+
+async fn save_badge(session: &Arc<Session>, admin_id: Uuid, badge: Badge, action: &str) -> (StatusCode, Json<ReturnType>) {
+    if badge.save(session).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("editBadge#0x01 Internal server error"))));
+    }
+    AuditEntry::record_logged(session, admin_id, action, None, Some(badge.badge_id.to_string())).await;
+    (StatusCode::OK, Json(ReturnType::ReturnBadge(badge)))
+}
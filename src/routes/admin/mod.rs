@@ -0,0 +1,9 @@
+pub mod searchusers;
+pub mod getuser;
+pub mod forcepasswordreset;
+pub mod revokesessions;
+pub mod badges;
+pub mod setadmin;
+pub mod auditlog;
+pub mod suspend;
+pub mod managebadges;
\ No newline at end of file
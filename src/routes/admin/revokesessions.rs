@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::gateway::Gateway;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    Ok,
+    Error(RequestError),
+}
+
+/// Revokes the session and every API token of the user and closes the gateway connections.
+pub async fn revoke_sessions(
+    State(session): State<Arc<Session>>,
+    Extension(gateway): Extension<Gateway>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let Ok(user) = User::from_user_id(user_id).fill_info(&session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+
+    if let Err(err) = user.revoke_sessions(&session).await {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from(err.to_string()))))
+    }else{
+        gateway.disconnect(user_id);
+        AuditEntry::record_logged(&session, admin.user_id.unwrap_or(Uuid::nil()), "revoke_sessions", Some(user_id), None).await;
+        (StatusCode::OK, Json(ReturnType::Ok))
+    }
+}
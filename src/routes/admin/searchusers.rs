@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use futures::TryStreamExt;
+use scylla::{DeserializeRow, Session};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::types::RequestError;
+
+const MAX_RESULTS: usize = 50;
+
+#[derive(Deserialize)]
+pub struct RequestSearch {
+    query: String,
+}
+
+#[derive(Serialize, DeserializeRow)]
+pub struct UserMatch {
+    user_id: Uuid,
+    username: String,
+    displayname: String,
+    email: String,
+    isadmin: Option<bool>,
+    isbot: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnUsers(Vec<UserMatch>),
+    Error(RequestError),
+}
+
+/// Searches users by user id, username, display name or e-mail (case-insensitive substring match).
+///
+/// Returns at most 50 matches.
+pub async fn search_users(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestSearch>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let query = payload.query.trim().to_lowercase();
+    if query.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Not every field satisfied"))));
+    }
+    // Results are only shown once the search is on record
+    if AuditEntry::record(&session, admin.user_id.unwrap_or(Uuid::nil()), "search_users", None, Some(query.clone())).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("searchUsers#0x02 Internal server error"))));
+    }
+
+    match find_users(&session, &query).await {
+        Ok(users) => (StatusCode::OK, Json(ReturnType::ReturnUsers(users))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("searchUsers#0x01 Internal server error")))),
+    }
+}
+
+// Walks through every user page by page, admin search is rare enough to afford a scan
+async fn find_users(session: &Arc<Session>, query: &str) -> anyhow::Result<Vec<UserMatch>> {
+    let mut rows = session.query_iter("SELECT user_id, username, displayname, email, isadmin, isbot FROM joltamp.users", ())
+        .await?.rows_stream::<UserMatch>()?;
+    let mut users = Vec::new();
+    while let Some(user) = rows.try_next().await? {
+        if user.user_id.to_string() == query
+            || user.username.to_lowercase().contains(query)
+            || user.displayname.to_lowercase().contains(query)
+            || user.email.to_lowercase().contains(query) {
+            users.push(user);
+            if users.len() >= MAX_RESULTS {
+                break;
+            }
+        }
+    }
+    Ok(users)
+}
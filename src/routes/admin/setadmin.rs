@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Deserialize)]
+pub struct RequestAdmin {
+    isadmin: bool,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    Ok,
+    Error(RequestError),
+}
+
+/// Grants or revokes admin rights of the user.
+///
+/// Admins cannot change their own admin flag, so the last admin cannot lock everyone out.
+pub async fn set_admin(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<RequestAdmin>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if admin.user_id == Some(user_id) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Cannot change own admin rights"))));
+    }
+    let Ok(user) = User::from_user_id(user_id).fill_info(&session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+
+    if let Err(err) = user.set_admin(&session, payload.isadmin).await {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from(err.to_string()))))
+    }else{
+        AuditEntry::record_logged(&session, admin.user_id.unwrap_or(Uuid::nil()), "set_admin", Some(user_id), Some(payload.isadmin.to_string())).await;
+        (StatusCode::OK, Json(ReturnType::Ok))
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::gateway::Gateway;
+use crate::security::auth::require_admin;
+use crate::types::audit::AuditEntry;
+use crate::types::suspension::Suspension;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Deserialize)]
+pub struct RequestSuspension {
+    reason: String,
+    until: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnSuspension(Suspension),
+    Ok,
+    Error(RequestError),
+}
+
+/// Suspends the user with a reason and an optional end time, revoking all of their sessions.
+///
+/// Gateway connections of the user and of their bots are closed. Without `until` the
+/// suspension is permanent until lifted with `unsuspend`.
+pub async fn suspend(
+    State(session): State<Arc<Session>>,
+    Extension(gateway): Extension<Gateway>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<RequestSuspension>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let admin_id = admin.user_id.unwrap_or(Uuid::nil());
+    if admin_id == user_id {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Cannot suspend yourself"))));
+    }
+    if payload.reason.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Not every field satisfied"))));
+    }
+    if payload.until.is_some_and(|until| until <= Utc::now()) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Suspension end is in the past"))));
+    }
+    let Ok(user) = User::from_user_id(user_id).fill_info(&session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+
+    let Ok(suspension) = Suspension::create(&session, user_id, admin_id, payload.reason, payload.until).await else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("suspend#0x01 Internal server error"))));
+    };
+    if user.revoke_sessions(&session).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("suspend#0x02 Internal server error"))));
+    }
+    gateway.disconnect_with_bots(&session, user_id).await;
+    AuditEntry::record_logged(&session, admin_id, "suspend", Some(user_id), Some(suspension.reason.clone())).await;
+    (StatusCode::OK, Json(ReturnType::ReturnSuspension(suspension)))
+}
+
+/// Lifts the suspension of the user.
+pub async fn unsuspend(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let admin = match require_admin(&session, &headers).await {
+        Ok(admin) => admin,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+
+    if Suspension::lift(&session, user_id).await.is_err() {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("unsuspend#0x01 Internal server error"))))
+    }else{
+        AuditEntry::record_logged(&session, admin.user_id.unwrap_or(Uuid::nil()), "unsuspend", Some(user_id), None).await;
+        (StatusCode::OK, Json(ReturnType::Ok))
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::functions::attachments::{sanitize_filename, MAX_ATTACHMENT_SIZE};
+use crate::security::auth::authorize;
+use crate::types::attachment::{Attachment, UPLOAD_SLOT_SECONDS};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestSlot {
+    filename: String,
+    size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnSlot{
+        #[serde(flatten)]
+        attachment: Attachment,
+        upload_url: String,
+        expiresat: DateTime<Utc>,
+    },
+    Error(RequestError),
+}
+
+/// Reserves an upload slot for an attachment.
+///
+/// The bytes of the file are then sent with `PUT` to `upload_url` before `expiresat`,
+/// they must be exactly `size` bytes long.
+///
+/// Messages do not exist yet, so uploaded attachments cannot be referenced when sending one.
+/// That part of the feature is outstanding until messages are added.
+///
+/// # Returns
+///
+/// * `StatusCode::CREATED`: With the pending attachment and where to upload it.
+/// * `StatusCode::BAD_REQUEST`: If the filename or size is invalid.
+/// * `StatusCode::PAYLOAD_TOO_LARGE`: If the file is larger than the limit.
+pub async fn create_attachment(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestSlot>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::SendMessages).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let filename = match sanitize_filename(&payload.filename) {
+        Ok(filename) => filename,
+        Err(err) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+    };
+    if payload.size <= 0 {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("File is empty"))));
+    }
+    if payload.size > MAX_ATTACHMENT_SIZE as i64 {
+        #[derive(Serialize)]
+        struct Details {
+            max_size: usize,
+        }
+        let err = RequestError::from("File is too large")
+            .with_code("file_too_large", Details { max_size: MAX_ATTACHMENT_SIZE });
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(ReturnType::Error(err)));
+    }
+
+    match Attachment::create(&session, user.user_id.unwrap_or(Uuid::nil()), filename, payload.size).await {
+        Ok(attachment) => (StatusCode::CREATED, Json(ReturnType::ReturnSlot {
+            upload_url: format!("/api/v0/attachments/{}/upload", attachment.attachment_id),
+            expiresat: attachment.createdat + Duration::seconds(UPLOAD_SLOT_SECONDS as i64),
+            attachment,
+        })),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("attachment#0x01 Internal server error")))),
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use chrono::Utc;
+use scylla::Session;
+use serde::Deserialize;
+use uuid::Uuid;
+use crate::functions::attachments::{is_image, verify_download};
+use crate::storage::Storage;
+use crate::types::attachment::{Attachment, ATTACHMENT_UPLOADED};
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestQuery {
+    expires: i64,
+    signature: String,
+}
+
+/// Serves an attachment through a signed URL from `/attachments/{id}/url`.
+///
+/// Downloads live under their own `file` segment, so no filename can collide with `url` or `upload`.
+///
+/// Images are shown inline, every other file is sent as a download. The stored content type is
+/// the sniffed one, and browsers are told not to guess another.
+pub async fn download_attachment(
+    State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    Path((attachment_id, filename)): Path<(Uuid, String)>,
+    Query(query): Query<RequestQuery>,
+) -> Response {
+    if !verify_download(attachment_id, query.expires, &query.signature) {
+        return (StatusCode::FORBIDDEN, Json(RequestError::from("Invalid signature"))).into_response();
+    }
+    if query.expires <= Utc::now().timestamp() {
+        return (StatusCode::GONE, Json(RequestError::from("Download link expired"))).into_response();
+    }
+    let attachment = match Attachment::from_attachment_id(&session, attachment_id).await {
+        Ok(attachment) if attachment.status == ATTACHMENT_UPLOADED && attachment.filename == filename => attachment,
+        _ => return (StatusCode::NOT_FOUND, Json(RequestError::from("Attachment not found"))).into_response(),
+    };
+
+    let content_type = attachment.content_type.clone().unwrap_or_else(|| String::from("application/octet-stream"));
+    let disposition = if is_image(&content_type) { "inline" } else { "attachment" };
+    match storage.get(&attachment.key()).await {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::CONTENT_DISPOSITION, format!("{}; filename=\"{}\"", disposition, attachment.filename)),
+                (header::X_CONTENT_TYPE_OPTIONS, String::from("nosniff")),
+                (header::CACHE_CONTROL, String::from("private, no-store")),
+            ],
+            bytes,
+        ).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, Json(RequestError::from("Attachment not found"))).into_response(),
+    }
+}
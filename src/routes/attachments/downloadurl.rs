@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::functions::attachments::{sign_download, DOWNLOAD_URL_SECONDS};
+use crate::security::auth::authorize;
+use crate::types::attachment::{Attachment, ATTACHMENT_UPLOADED};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnUrl{
+        url: String,
+        expiresat: DateTime<Utc>,
+    },
+    Error(RequestError),
+}
+
+/// Returns a signed download URL of an uploaded attachment.
+///
+/// The URL needs no authorization header, so it can be used directly as a link or image source,
+/// and stops working at `expiresat`. Only the uploader can request one for now.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the URL and its expiry.
+/// * `StatusCode::NOT_FOUND`: If the attachment does not exist, is not uploaded yet or belongs to someone else.
+pub async fn get_download_url(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(attachment_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::SendMessages).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let attachment = match Attachment::from_attachment_id(&session, attachment_id).await {
+        Ok(attachment) if Some(attachment.owner_id) == user.user_id && attachment.status == ATTACHMENT_UPLOADED => attachment,
+        _ => return (StatusCode::NOT_FOUND, Json(ReturnType::Error(RequestError::from("Attachment not found")))),
+    };
+
+    let expiresat = Utc::now() + Duration::seconds(DOWNLOAD_URL_SECONDS);
+    let expires = expiresat.timestamp();
+    let url = format!("/api/v0/attachments/{}/file/{}?expires={}&signature={}",
+                      attachment.attachment_id, attachment.filename, expires, sign_download(attachment.attachment_id, expires));
+    (StatusCode::OK, Json(ReturnType::ReturnUrl { url, expiresat }))
+}
@@ -0,0 +1,4 @@
+pub mod createattachment;
+pub mod uploadattachment;
+pub mod downloadurl;
+pub mod download;
\ No newline at end of file
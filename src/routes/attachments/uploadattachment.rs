@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::functions::attachments::{image_dimensions, is_image, sniff_content_type, MAX_IMAGE_ATTACHMENT_SIZE};
+use crate::security::auth::authorize;
+use crate::storage::Storage;
+use crate::types::attachment::{Attachment, ATTACHMENT_PENDING};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnAttachment(Attachment),
+    Error(RequestError),
+}
+
+/// Uploads the bytes of an attachment to its slot, sent as the raw request body.
+///
+/// The content type is sniffed from the bytes, images also get their width and height.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the uploaded attachment.
+/// * `StatusCode::NOT_FOUND`: If the slot does not exist, expired or belongs to someone else.
+/// * `StatusCode::CONFLICT`: If the bytes were already uploaded.
+/// * `StatusCode::BAD_REQUEST`: If the body does not have the size given when creating the slot.
+/// * `StatusCode::PAYLOAD_TOO_LARGE`: If the file is an image larger than the image limit.
+pub async fn upload_attachment(
+    State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    headers: HeaderMap,
+    Path(attachment_id): Path<Uuid>,
+    body: Bytes,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::SendMessages).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let mut attachment = match Attachment::from_attachment_id(&session, attachment_id).await {
+        Ok(attachment) if Some(attachment.owner_id) == user.user_id => attachment,
+        _ => return (StatusCode::NOT_FOUND, Json(ReturnType::Error(RequestError::from("Attachment not found")))),
+    };
+    if attachment.status != ATTACHMENT_PENDING {
+        return (StatusCode::CONFLICT, Json(ReturnType::Error(RequestError::from("Attachment was already uploaded"))));
+    }
+    if body.len() as i64 != attachment.size {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("File size does not match the upload slot"))));
+    }
+
+    let content_type = sniff_content_type(&body);
+    let dimensions = if is_image(content_type) {
+        if body.len() > MAX_IMAGE_ATTACHMENT_SIZE {
+            return (StatusCode::PAYLOAD_TOO_LARGE, Json(ReturnType::Error(RequestError::from("Image is too large"))));
+        }
+        image_dimensions(&body)
+    } else {
+        None
+    };
+
+    if storage.put(&attachment.key(), body.to_vec()).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("uploadAttachment#0x01 Internal server error"))));
+    }
+    match attachment.complete_upload(&session, content_type, dimensions).await {
+        Ok(()) => (StatusCode::OK, Json(ReturnType::ReturnAttachment(attachment))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("uploadAttachment#0x02 Internal server error")))),
+    }
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use scylla::Session;
+use serde::Serialize;
+use crate::types::badge::Badge;
+use crate::types::types::RequestError;
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnBadges(Vec<Badge>),
+    Error(RequestError),
+}
+
+/// Returns the badge catalog without retired badges, sorted by display priority.
+pub async fn get_badges(
+    State(session): State<Arc<Session>>,
+) -> (StatusCode, Json<ReturnType>) {
+    match Badge::list_all(&session, false).await {
+        Ok(badges) => (StatusCode::OK, Json(ReturnType::ReturnBadges(badges))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("badges#0x01 Internal server error")))),
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::types::badge::Badge;
+use crate::types::types::RequestError;
+
+const MAX_LOOKUP: usize = 100;
+
+#[derive(Deserialize)]
+pub struct RequestBadges {
+    ids: Vec<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnBadges(Vec<Badge>),
+    Error(RequestError),
+}
+
+/// Resolves a set of badge ids (retired badges included), unknown ids are left out.
+pub async fn lookup_badges(
+    State(session): State<Arc<Session>>,
+    Json(payload): Json<RequestBadges>,
+) -> (StatusCode, Json<ReturnType>) {
+    if payload.ids.len() > MAX_LOOKUP {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(format!("Too many ids (>{})", MAX_LOOKUP)))));
+    }
+    match Badge::from_badge_ids(&session, &payload.ids).await {
+        Ok(badges) => (StatusCode::OK, Json(ReturnType::ReturnBadges(badges))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("badges#0x02 Internal server error")))),
+    }
+}
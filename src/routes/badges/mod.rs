@@ -0,0 +1,2 @@
+pub mod getbadges;
+pub mod lookupbadges;
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::functions::validation::normalize_username;
+use crate::routes::users::register::check_username_free;
+use crate::security::auth::authorize;
+use crate::types::search::SearchIndex;
+use crate::types::token::{ApiToken, Scope};
+use crate::types::types::RequestError;
+use crate::types::username::UsernameClaim;
+
+#[derive(Deserialize)]
+pub struct RequestBot {
+    username: String,
+    scopes: Option<Vec<Scope>>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnBot{
+        user_id: Uuid,
+        token_id: Uuid,
+        token: String,
+    },
+    Error(RequestError),
+}
+
+/// Creates a bot account owned by the user together with its first API token.
+///
+/// Bots have no e-mail or password and can only authenticate with `Bot <token>`.
+/// When `scopes` is not set the token gets every grantable scope.
+///
+/// # Returns
+///
+/// * `StatusCode::CREATED`: With the bot user id and its plain token.
+/// * `StatusCode::BAD_REQUEST`: If the username is invalid or already used.
+pub async fn create_bot(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestBot>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if user.isbot.unwrap_or(false) {
+        return (StatusCode::FORBIDDEN, Json(ReturnType::Error(RequestError::from("Bots cannot own bots"))));
+    }
+    let username = match normalize_username(&payload.username) {
+        Ok(username) => username,
+        Err(err) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+    };
+    let scopes = payload.scopes.unwrap_or(Scope::GRANTABLE.to_vec());
+    if scopes.iter().any(|scope| !Scope::GRANTABLE.contains(scope)) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Scope cannot be granted to a token"))));
+    }
+    match check_username_free(&session, &username, &String::new()).await {
+        Ok(false) => {}
+        Ok(true) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Username already used")))),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("createbot#0x01 Internal server error")))),
+    }
+
+    let bot_id = Uuid::new_v4();
+    match UsernameClaim::claim(&session, &username, bot_id).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Username already used")))),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("createbot#0x03 Internal server error")))),
+    }
+    // Bots never log in, the JWT only keeps the row consistent with user accounts
+    let res = session.query_unpaged("INSERT INTO joltamp.users (createdat, user_id, username, displayname, email, password, isadmin, jwt, status, isbot, owner_id) VALUES (todate(now()), ?, ?, ?, '', '', false, ?, 0, true, ?)",
+                                    (bot_id, &username, &username, Uuid::new_v4(), user.user_id)).await;
+    if res.is_err() {
+        let _ = UsernameClaim::release(&session, &username, bot_id).await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("createbot#0x02 Internal server error"))));
+    }
+    if SearchIndex::index(&session, bot_id, &username, &username).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("createbot#0x02 Internal server error"))));
+    }
+
+    match ApiToken::create(&session, bot_id, String::from("default"), scopes).await {
+        Ok((info, token)) => (StatusCode::CREATED, Json(ReturnType::ReturnBot { user_id: bot_id, token_id: info.token_id, token })),
+        Err(err) => (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string())))),
+    }
+}
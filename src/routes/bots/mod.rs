@@ -0,0 +1 @@
+pub mod createbot;
\ No newline at end of file
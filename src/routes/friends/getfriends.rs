@@ -4,17 +4,14 @@ use axum::extract::State;
 use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
 use scylla::Session;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use uuid::Uuid;
+use crate::security::auth::authorize;
 use crate::types::friend::Friend;
+use crate::types::token::Scope;
 use crate::types::types::RequestError;
 use crate::types::user::{User, UserFunc};
 
-#[derive(Deserialize)]
-pub struct RequestUser {
-
-}
-
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum ReturnType {
@@ -25,16 +22,15 @@ pub async fn get_friends(
     State(session): State<Arc<Session>>,
     headers: HeaderMap,
 ) -> (StatusCode, Json<ReturnType>) {
-    if let Some(auth ) = headers.get("Authorization") {
-        let user: User = User::from_user_jwt(Uuid::parse_str(auth.to_str().unwrap()).unwrap_or(Uuid::nil())).fill_info(&session).await.unwrap().fetch_friends(&session).await.unwrap();
-
-        if let Some(friends) = user.friends{
-            return (StatusCode::OK, Json(ReturnType::ReturnFriends(friends)));
-        }else{
-            return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Cannot fetch friends"))))
-        }
+    let user = match authorize(&session, &headers, Scope::ManageFriends).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let user = user.fetch_friends(&session).await;
 
+    if let Ok(User { friends: Some(friends), .. }) = user {
+        (StatusCode::OK, Json(ReturnType::ReturnFriends(friends)))
+    }else{
+        (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Cannot fetch friends"))))
     }
-
-    (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("Ok"))))
 }
\ No newline at end of file
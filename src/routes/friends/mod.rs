@@ -1 +1,3 @@
-pub mod getfriends;
\ No newline at end of file
+pub mod getfriends;
+pub mod nickname;
+pub mod suggestions;
\ No newline at end of file
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::functions::validation::normalize_displayname;
+use crate::security::auth::authorize;
+use crate::types::annotation::Annotation;
+use crate::types::friend::FRIEND_STATUS_FRIENDS;
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestNickname {
+    nickname: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnNickname{
+        user_id: Uuid,
+        nickname: Option<String>,
+    },
+    Error(RequestError),
+}
+
+/// Sets a private nickname for a friend, shown only to the user in their friend list.
+///
+/// A missing or empty `nickname` clears it. Nicknames follow the display name rules.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the stored nickname.
+/// * `StatusCode::BAD_REQUEST`: If the user is not a friend or the nickname is invalid.
+pub async fn set_nickname(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(friend_id): Path<Uuid>,
+    Json(payload): Json<RequestNickname>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageFriends).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if user.relations.as_ref().and_then(|relations| relations.get(&friend_id)) != Some(&FRIEND_STATUS_FRIENDS) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("User is not a friend"))));
+    }
+    let nickname = match payload.nickname.filter(|nickname| !nickname.trim().is_empty()) {
+        Some(nickname) => match normalize_displayname(&nickname) {
+            Ok(nickname) => Some(nickname),
+            Err(err) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+        },
+        None => None,
+    };
+
+    match Annotation::set_nickname(&session, user.user_id.unwrap_or(Uuid::nil()), friend_id, nickname.clone()).await {
+        Ok(()) => (StatusCode::OK, Json(ReturnType::ReturnNickname { user_id: friend_id, nickname })),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("nickname#0x01 Internal server error")))),
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use futures::{StreamExt, TryStreamExt};
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::security::auth::authorize;
+use crate::types::friend::{mutual_friends, Friend, FRIEND_STATUS_FRIENDS};
+use crate::types::privacy::Relation;
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+const MAX_SUGGESTIONS: usize = 20;
+// Database reads running at the same time for one request
+const MAX_CONCURRENT_READS: usize = 16;
+
+#[derive(Serialize)]
+pub struct Suggestion {
+    #[serde(flatten)]
+    user: Friend,
+    mutual_friends: Vec<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnSuggestions(Vec<Suggestion>),
+    Error(RequestError),
+}
+
+/// Suggests people the user may know: friends of friends ranked by the number of mutual friends.
+///
+/// Users the caller already has any relation with (friends, pending requests, blocks), users who
+/// blocked the caller or disabled discovery, and deactivated accounts are left out.
+///
+/// Shared communities are not part of the ranking yet: communities do not exist, so mutual
+/// friends are the only signal until they are added.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With at most 20 suggestions, shaped like friends with the mutual friend ids.
+pub async fn get_suggestions(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageFriends).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    match find_suggestions(&session, &user).await {
+        Ok(suggestions) => (StatusCode::OK, Json(ReturnType::ReturnSuggestions(suggestions))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("suggestions#0x01 Internal server error")))),
+    }
+}
+
+async fn find_suggestions(session: &Arc<Session>, user: &User) -> anyhow::Result<Vec<Suggestion>> {
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+    let empty = HashMap::new();
+    let relations = user.relations.as_ref().unwrap_or(&empty);
+    let friends: Vec<Uuid> = relations.iter()
+        .filter(|(_, status)| **status == FRIEND_STATUS_FRIENDS)
+        .map(|(id, _)| *id)
+        .collect();
+
+    // Count how many friends every friend of a friend shares with the user
+    let friend_maps: Vec<HashMap<Uuid, i8>> = futures::stream::iter(friends)
+        .map(|friend_id| async move {
+            let res = session.query_unpaged("SELECT friends FROM joltamp.users WHERE user_id = ? ALLOW FILTERING", (friend_id, ))
+                .await?.into_rows_result()?;
+            Ok::<_, anyhow::Error>(res.maybe_first_row::<(HashMap<Uuid, i8>, )>()?.map(|(friends, )| friends).unwrap_or_default())
+        })
+        .buffer_unordered(MAX_CONCURRENT_READS)
+        .try_collect().await?;
+    let mut counts: HashMap<Uuid, usize> = HashMap::new();
+    for friend_map in &friend_maps {
+        for (candidate, status) in friend_map {
+            if *status == FRIEND_STATUS_FRIENDS && *candidate != user_id && !relations.contains_key(candidate) {
+                *counts.entry(*candidate).or_default() += 1;
+            }
+        }
+    }
+    let mut candidates: Vec<(Uuid, usize)> = counts.into_iter().collect();
+    candidates.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    // Candidates are checked in rank order until enough of them are visible to the user
+    let mut suggestions = Vec::new();
+    let mut checked = futures::stream::iter(candidates)
+        .map(|(candidate, _)| User::from_user_id(candidate).fill_info(session))
+        .buffered(MAX_CONCURRENT_READS);
+    while let Some(candidate) = checked.next().await {
+        let Ok(candidate) = candidate else { continue };
+        let relation = Relation::between(Some(user), &candidate);
+        if candidate.deactivated.unwrap_or(false) || candidate.deleted.unwrap_or(false)
+            || !candidate.privacy.unwrap_or_default().discoverable_by(relation) {
+            continue;
+        }
+        let mutual = mutual_friends(relations, candidate.relations.as_ref().unwrap_or(&empty));
+        suggestions.push(Suggestion { user: Friend::for_viewer(candidate, relation), mutual_friends: mutual });
+        if suggestions.len() == MAX_SUGGESTIONS {
+            break;
+        }
+    }
+    Ok(suggestions)
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+use crate::gateway::{Gateway, GatewayEvent};
+use crate::security::auth::authorize;
+use crate::types::token::Scope;
+
+#[derive(Deserialize)]
+pub struct RequestQuery {
+    token: Option<String>,
+}
+
+/// Opens the gateway websocket of the authenticated user.
+///
+/// Browsers cannot set headers on websocket requests, so the credential can also be passed
+/// as `?token=`, in any of the formats accepted by the `Authorization` header.
+/// The server sends a `READY` event after connecting and then every event published for the user.
+pub async fn connect(
+    State(session): State<Arc<Session>>,
+    Extension(gateway): Extension<Gateway>,
+    mut headers: HeaderMap,
+    Query(query): Query<RequestQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    if let Some(token) = query.token.and_then(|token| HeaderValue::from_str(&token).ok()) {
+        headers.insert("Authorization", token);
+    }
+    let user = match authorize(&session, &headers, Scope::ReadProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(err)).into_response(),
+    };
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+    upgrade.on_upgrade(move |socket| serve(socket, gateway, user_id))
+}
+
+async fn serve(mut socket: WebSocket, gateway: Gateway, user_id: Uuid) {
+    let mut events = gateway.subscribe(user_id);
+    forward(&mut socket, &mut events, user_id).await;
+    gateway.unsubscribe(user_id, events);
+}
+
+async fn forward(socket: &mut WebSocket, events: &mut broadcast::Receiver<GatewayEvent>, user_id: Uuid) {
+    let ready = serde_json::json!({ "t": "READY", "d": { "user_id": user_id } });
+    if socket.send(Message::Text(ready.to_string().into())).await.is_err() {
+        return;
+    }
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        return;
+                    }
+                }
+                // The client fell behind and missed events, it has to reconnect and resync
+                Err(RecvError::Lagged(_)) => return,
+                // The user was disconnected, e.g. because the sessions were revoked
+                Err(RecvError::Closed) => {
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+            },
+            message = socket.recv() => match message {
+                // Clients only listen, incoming messages are ignored (pings are answered by axum)
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod connect;
\ No newline at end of file
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use axum::extract::{Path, Query};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use serde::Deserialize;
+use crate::functions::images::ImageKind;
+use crate::storage::Storage;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestQuery {
+    size: Option<u32>,
+}
+
+/// Serves a stored profile image, `kind` is `avatars` or `banners`.
+///
+/// Without `?size=` the largest size is returned. Images are content addressed,
+/// so they can be cached forever.
+pub async fn get_media(
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    Path((kind, hash)): Path<(String, String)>,
+    Query(query): Query<RequestQuery>,
+) -> Response {
+    let Some(kind) = ImageKind::parse(&kind) else {
+        return (StatusCode::NOT_FOUND, Json(RequestError::from("Unknown media kind"))).into_response();
+    };
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return (StatusCode::BAD_REQUEST, Json(RequestError::from("Invalid hash"))).into_response();
+    }
+    let size = query.size.unwrap_or(kind.sizes()[0]);
+    if !kind.sizes().contains(&size) {
+        return (StatusCode::BAD_REQUEST, Json(RequestError::from(format!("Size must be one of {:?}", kind.sizes())))).into_response();
+    }
+
+    match storage.get(&kind.key(&hash, size)).await {
+        Ok(bytes) => (
+            [(header::CONTENT_TYPE, "image/png"), (header::CACHE_CONTROL, "public, max-age=31536000, immutable")],
+            bytes,
+        ).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, Json(RequestError::from("Image not found"))).into_response(),
+    }
+}
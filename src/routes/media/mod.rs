@@ -0,0 +1,2 @@
+pub mod uploadimage;
+pub mod getmedia;
\ No newline at end of file
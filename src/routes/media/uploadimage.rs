@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use axum::extract::{Multipart, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use scylla::frame::response::result::CqlValue;
+use scylla::Session;
+use serde::Serialize;
+use crate::functions::images::{process_image, ImageKind, MAX_UPLOAD_SIZE};
+use crate::security::auth::authorize;
+use crate::storage::Storage;
+use crate::types::deletion::remove_image;
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+use crate::types::user::UserFunc;
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnImage{
+        hash: String,
+        sizes: Vec<u32>,
+    },
+    Error(RequestError),
+}
+
+/// Uploads a new avatar, sent as multipart form with the image in the `file` field.
+///
+/// The image is served at `/api/v0/media/avatars/{hash}?size={size}`.
+pub async fn upload_avatar(
+    State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> (StatusCode, Json<ReturnType>) {
+    upload_image(&session, storage, &headers, multipart, ImageKind::Avatar).await
+}
+
+/// Uploads a new banner, sent as multipart form with the image in the `file` field.
+///
+/// The image is served at `/api/v0/media/banners/{hash}?size={size}`.
+pub async fn upload_banner(
+    State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> (StatusCode, Json<ReturnType>) {
+    upload_image(&session, storage, &headers, multipart, ImageKind::Banner).await
+}
+
+async fn upload_image(session: &Arc<Session>, storage: Arc<dyn Storage>, headers: &HeaderMap, mut multipart: Multipart, kind: ImageKind) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(session, headers, Scope::WriteProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+
+    let mut bytes = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() == Some("file") {
+            bytes = field.bytes().await.ok();
+            break;
+        }
+    }
+    let Some(bytes) = bytes else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Missing file"))));
+    };
+    if bytes.len() > MAX_UPLOAD_SIZE {
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(ReturnType::Error(RequestError::from("Image is too large"))));
+    }
+
+    // Decoding and resizing is CPU heavy, keep it off the async workers
+    let processed = match tokio::task::spawn_blocking(move || process_image(kind, &bytes)).await {
+        Ok(Ok(processed)) => processed,
+        Ok(Err(err)) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("upload#0x01 Internal server error")))),
+    };
+
+    let sizes = processed.variants.iter().map(|(size, _)| *size).collect();
+    for (size, variant) in processed.variants {
+        let key = kind.key(&processed.hash, size);
+        // Content addressed, an identical image is already stored
+        if storage.exists(&key).await.unwrap_or(false) {
+            continue;
+        }
+        if storage.put(&key, variant).await.is_err() {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("upload#0x02 Internal server error"))));
+        }
+    }
+
+    let old_hash = match kind {
+        ImageKind::Avatar => user.avatar.clone(),
+        ImageKind::Banner => user.banner.clone(),
+    };
+    if user.update_fields(session, vec![(kind.column(), CqlValue::Text(processed.hash.clone()))]).await.is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("upload#0x03 Internal server error"))));
+    }
+    // The replaced image stays stored if another user still has it
+    if let Some(old_hash) = old_hash.filter(|old_hash| *old_hash != processed.hash) {
+        if let Err(err) = remove_image(session, &storage, kind, &old_hash).await {
+            tracing::warn!("Failed to remove replaced image {}: {}", old_hash, err);
+        }
+    }
+    (StatusCode::OK, Json(ReturnType::ReturnImage { hash: processed.hash, sizes }))
+}
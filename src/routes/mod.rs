@@ -1,2 +1,9 @@
 pub mod users;
-pub mod friends;
\ No newline at end of file
+pub mod friends;
+pub mod tokens;
+pub mod bots;
+pub mod admin;
+pub mod badges;
+pub mod media;
+pub mod gateway;
+pub mod attachments;
\ No newline at end of file
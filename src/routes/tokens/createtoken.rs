@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::security::auth::authorize;
+use crate::types::token::{ApiToken, Scope};
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Deserialize)]
+pub struct RequestToken {
+    name: String,
+    scopes: Vec<Scope>,
+    #[serde(rename = "botId")]
+    bot_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnToken{
+        token: String,
+        #[serde(flatten)]
+        info: ApiToken,
+    },
+    Error(RequestError),
+}
+
+/// Creates a personal API token for the user, or for one of their bots when `botId` is set.
+///
+/// The plain token is only returned by this call, afterwards only its metadata can be listed.
+///
+/// # Returns
+///
+/// * `StatusCode::CREATED`: With the plain token and its metadata.
+/// * `StatusCode::BAD_REQUEST`: If the name is empty or a requested scope cannot be granted.
+/// * `StatusCode::FORBIDDEN`: If the bot does not belong to the user.
+pub async fn create_token(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestToken>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if payload.name.trim().is_empty() || payload.scopes.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Not every field satisfied"))));
+    }
+    let owner_id = match owned_user_id(&session, &user, payload.bot_id).await {
+        Ok(owner_id) => owner_id,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+
+    match ApiToken::create(&session, owner_id, payload.name, payload.scopes).await {
+        Ok((info, token)) => (StatusCode::CREATED, Json(ReturnType::ReturnToken { token, info })),
+        Err(err) => (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string())))),
+    }
+}
+
+/// Resolves whose tokens are managed: the user itself, or their bot when `bot_id` is set
+pub async fn owned_user_id(session: &Arc<Session>, user: &User, bot_id: Option<Uuid>) -> Result<Uuid, (StatusCode, RequestError)> {
+    let Some(bot_id) = bot_id else {
+        return Ok(user.user_id.unwrap_or(Uuid::nil()));
+    };
+    let bot = User::from_user_id(bot_id).fill_info(session).await;
+    match bot {
+        Ok(bot) if bot.isbot.unwrap_or(false) && bot.owner_id == user.user_id => Ok(bot_id),
+        _ => Err((StatusCode::FORBIDDEN, RequestError::from("Bot not owned by user"))),
+    }
+}
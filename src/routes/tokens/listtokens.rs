@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::routes::tokens::createtoken::owned_user_id;
+use crate::security::auth::authorize;
+use crate::types::token::{ApiToken, Scope};
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestQuery {
+    #[serde(rename = "botId")]
+    bot_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnTokens(Vec<ApiToken>),
+    Error(RequestError),
+}
+
+/// Lists the API tokens of the user, or of one of their bots when `botId` is set.
+///
+/// Plain token values are never returned, only metadata including last usage.
+pub async fn list_tokens(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Query(query): Query<RequestQuery>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let owner_id = match owned_user_id(&session, &user, query.bot_id).await {
+        Ok(owner_id) => owner_id,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+
+    if let Ok(tokens) = ApiToken::list_for_user(&session, owner_id).await {
+        (StatusCode::OK, Json(ReturnType::ReturnTokens(tokens)))
+    }else{
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("tokens#0x01 Internal server error"))))
+    }
+}
@@ -0,0 +1,3 @@
+pub mod createtoken;
+pub mod listtokens;
+pub mod revoketoken;
\ No newline at end of file
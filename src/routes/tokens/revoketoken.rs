@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::gateway::Gateway;
+use crate::routes::tokens::createtoken::owned_user_id;
+use crate::security::auth::authorize;
+use crate::types::token::{ApiToken, Scope};
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestToken {
+    #[serde(rename = "tokenId")]
+    token_id: Uuid,
+    #[serde(rename = "botId")]
+    bot_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    Ok,
+    Error(RequestError),
+}
+
+/// Revokes an API token of the user, or of one of their bots when `botId` is set.
+///
+/// Connections do not know the credential they were opened with, so every gateway connection
+/// of the token owner is closed and clients with a still valid credential reconnect.
+pub async fn revoke_token(
+    State(session): State<Arc<Session>>,
+    Extension(gateway): Extension<Gateway>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestToken>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let owner_id = match owned_user_id(&session, &user, payload.bot_id).await {
+        Ok(owner_id) => owner_id,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+
+    if let Err(err) = ApiToken::revoke(&session, owner_id, payload.token_id).await {
+        (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string()))))
+    }else{
+        gateway.disconnect(owner_id);
+        (StatusCode::OK, Json(ReturnType::Ok))
+    }
+}
@@ -1,46 +1,73 @@
 use std::sync::Arc;
 use axum::extract::State;
 use axum::http::{HeaderMap, StatusCode};
-use axum::Json;
+use axum::{Extension, Json};
 use scylla::Session;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::notifier::Notifier;
+use crate::security::auth::authorize;
+use crate::security::policy::PasswordPolicyError;
+use crate::security::reauth::{record_security_event, verify_reauth, SENSITIVE_FIELDS};
+use crate::types::token::Scope;
 use crate::types::types::{RequestError};
-use crate::types::user::{User, UserFunc};
+use crate::types::user::UserFunc;
 
 #[derive(Deserialize)]
 pub struct RequestUser {
     field: String,
-    newValue: String,
+    #[serde(rename = "newValue")]
+    new_value: String,
+    #[serde(rename = "currentPassword")]
+    current_password: Option<String>,
+    #[serde(rename = "reauthToken")]
+    reauth_token: Option<Uuid>,
 }
 
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum ReturnType {
     Ok,
+    PasswordRejected(PasswordPolicyError),
     Error(RequestError),
 }
 pub async fn change_selfinfo(
     State(session): State<Arc<Session>>,
+    Extension(notifier): Extension<Arc<dyn Notifier>>,
     headers: HeaderMap,
     Json(payload): Json<RequestUser>,
 ) -> (StatusCode, Json<ReturnType>) {
-    
-    if let Some(auth ) = headers.get("Authorization") {
-        // Fetch user from db based on provided jwt
-        let user = User::from_user_jwt(Uuid::parse_str(auth.to_str().unwrap_or("")).unwrap_or(Uuid::nil())).fill_info(&session).await;
-        if let Ok(user) = user{
-            let res = user.update(&session, payload.field.as_str(), payload.newValue).await;
-            if let Err(err) = &res {
-                (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string()))))
-            }else{
-                (StatusCode::OK, Json(ReturnType::Ok))
-            }
-        }else{
-            (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("User JWT not found"))))
+    let user = match authorize(&session, &headers, Scope::WriteProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    // Validated before the re-auth token is spent, so a rejected value does not cost the token
+    if let Err(err) = user.validate_update(payload.field.as_str(), &payload.new_value) {
+        return match err.downcast::<PasswordPolicyError>() {
+            Ok(policy_err) => (StatusCode::BAD_REQUEST, Json(ReturnType::PasswordRejected(policy_err))),
+            Err(err) => (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string())))),
+        };
+    }
+    // Sensitive fields require proof of knowing the current password
+    let sensitive = SENSITIVE_FIELDS.contains(&payload.field.as_str());
+    if sensitive {
+        if let Err(err) = verify_reauth(&session, &user, payload.current_password.as_deref(), payload.reauth_token).await {
+            return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from(err.to_string()))));
+        }
+    }
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+    let old_email = user.email.clone().unwrap_or_default();
+    let res = user.update(&session, payload.field.as_str(), payload.new_value).await;
+    if let Err(err) = res {
+        // Password policy violations carry structured reasons for the client
+        match err.downcast::<PasswordPolicyError>() {
+            Ok(policy_err) => (StatusCode::BAD_REQUEST, Json(ReturnType::PasswordRejected(policy_err))),
+            Err(err) => (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string())))),
         }
     }else{
-        (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Invalid JWT"))))
+        if sensitive {
+            let _ = record_security_event(&session, &notifier, user_id, &old_email, &format!("{}_changed", payload.field)).await;
+        }
+        (StatusCode::OK, Json(ReturnType::Ok))
     }
-
 }
\ No newline at end of file
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use chrono::{DateTime, Duration, Utc};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::routes::users::register::username_owner;
+use crate::functions::validation::normalize_username;
+use crate::notifier::Notifier;
+use crate::security::auth::authorize;
+use crate::security::reauth::{record_security_event, verify_reauth};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+use crate::types::user::UserFunc;
+use crate::types::username::{UsernameChange, UsernameTaken, USERNAME_COOLDOWN_DAYS};
+
+#[derive(Deserialize)]
+pub struct RequestUsername {
+    username: String,
+    #[serde(rename = "currentPassword")]
+    current_password: Option<String>,
+    #[serde(rename = "reauthToken")]
+    reauth_token: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnUsername{
+        username: String,
+        previous_username: String,
+        next_change_at: DateTime<Utc>,
+    },
+    Error(RequestError),
+}
+
+/// Changes the username of the authenticated user.
+///
+/// Requires re-authentication like other sensitive fields. A username can be changed once per
+/// cooldown period, the previous one stays reserved for its former owner for a grace period.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the new username and the earliest time of the next change.
+/// * `StatusCode::BAD_REQUEST`: If the username is invalid or taken.
+/// * `StatusCode::TOO_MANY_REQUESTS`: If the cooldown is not over, with code `username_cooldown`.
+pub async fn change_username(
+    State(session): State<Arc<Session>>,
+    Extension(notifier): Extension<Arc<dyn Notifier>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestUsername>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let username = match normalize_username(&payload.username) {
+        Ok(username) => username,
+        Err(err) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+    };
+    if Some(&username) == user.username.as_ref() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Username unchanged"))));
+    }
+    if let Err(err) = verify_reauth(&session, &user, payload.current_password.as_deref(), payload.reauth_token).await {
+        return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from(err.to_string()))));
+    }
+
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+    match UsernameChange::cooldown_until(&session, user_id).await {
+        Ok(None) => {}
+        Ok(Some(until)) => {
+            #[derive(Serialize)]
+            struct Details {
+                retry_after: DateTime<Utc>,
+            }
+            let err = RequestError::from("Username was changed recently")
+                .with_code("username_cooldown", Details { retry_after: until });
+            return (StatusCode::TOO_MANY_REQUESTS, Json(ReturnType::Error(err)));
+        }
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("changeUsername#0x01 Internal server error")))),
+    }
+    // Previous usernames of the same user can be taken back during their reservation
+    match username_owner(&session, &username).await {
+        Ok(None) => {}
+        Ok(Some(owner)) if owner == user_id => {}
+        Ok(Some(_)) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Username already used")))),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("changeUsername#0x02 Internal server error")))),
+    }
+
+    let previous_username = user.username.clone().unwrap_or_default();
+    let email = user.email.clone().unwrap_or_default();
+    match user.change_username(&session, username).await {
+        Ok(user) => {
+            let _ = record_security_event(&session, &notifier, user_id, &email, "username_changed").await;
+            (StatusCode::OK, Json(ReturnType::ReturnUsername {
+                username: user.username.unwrap_or_default(),
+                previous_username,
+                next_change_at: Utc::now() + Duration::days(USERNAME_COOLDOWN_DAYS),
+            }))
+        }
+        Err(err) if err.is::<UsernameTaken>() => (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Username already used")))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("changeUsername#0x03 Internal server error")))),
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::gateway::Gateway;
+use crate::notifier::Notifier;
+use crate::security::auth::authorize;
+use crate::security::reauth::{record_security_event, verify_reauth};
+use crate::types::deletion::AccountDeletion;
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestDeletion {
+    #[serde(rename = "currentPassword")]
+    current_password: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnDeletion{
+        purge_at: DateTime<Utc>,
+    },
+    Error(RequestError),
+}
+
+/// Requests deletion of the own account.
+///
+/// The account is deactivated, every session revoked and the gateway connections of the user
+/// and their bots closed right away. Logging in before `purge_at` cancels the deletion,
+/// afterwards the account is permanently removed.
+///
+/// # Returns
+///
+/// * `StatusCode::ACCEPTED`: With the time the account will be removed.
+/// * `StatusCode::UNAUTHORIZED`: If the current password is wrong.
+pub async fn delete_self(
+    State(session): State<Arc<Session>>,
+    Extension(notifier): Extension<Arc<dyn Notifier>>,
+    Extension(gateway): Extension<Gateway>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestDeletion>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if user.isbot.unwrap_or(false) {
+        return (StatusCode::FORBIDDEN, Json(ReturnType::Error(RequestError::from("Bots cannot delete themselves"))));
+    }
+    // Only the password is accepted, a re-auth token could have been obtained before the device was lost
+    if let Err(err) = verify_reauth(&session, &user, Some(&payload.current_password), None).await {
+        return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from(err.to_string()))));
+    }
+
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+    let email = user.email.clone().unwrap_or_default();
+    match AccountDeletion::request(&session, user).await {
+        Ok(deletion) => {
+            gateway.disconnect_with_bots(&session, user_id).await;
+            let _ = record_security_event(&session, &notifier, user_id, &email, "deletion_requested").await;
+            (StatusCode::ACCEPTED, Json(ReturnType::ReturnDeletion { purge_at: deletion.purgeat }))
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("deleteSelf#0x01 Internal server error")))),
+    }
+}
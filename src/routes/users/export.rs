@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use scylla::Session;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::security::auth::authorize;
+use crate::storage::Storage;
+use crate::types::export::{ExportJob, EXPORT_READY};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Serialize)]
+pub struct ExportStatus {
+    job_id: Uuid,
+    status: String,
+    createdat: DateTime<Utc>,
+    expiresat: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    download_url: Option<String>,
+}
+
+impl From<ExportJob> for ExportStatus {
+    fn from(job: ExportJob) -> Self {
+        ExportStatus {
+            job_id: job.job_id,
+            download_url: job.download_token.filter(|_| job.status == EXPORT_READY)
+                .map(|token| format!("/api/v0/exports/{}", token)),
+            status: job.status,
+            createdat: job.createdat,
+            expiresat: job.expiresat,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnExport(ExportStatus),
+    Error(RequestError),
+}
+
+/// Starts an export of every personal data stored about the user.
+///
+/// The archive is built in the background, its status is polled with `/users/@me/export/{id}`.
+/// While an export is pending or ready to download, it is returned instead of starting a new one.
+///
+/// # Returns
+///
+/// * `StatusCode::ACCEPTED`: With the new pending job.
+/// * `StatusCode::OK`: With the already running or downloadable job.
+pub async fn start_export(
+    State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    match ExportJob::start(&session, &storage, user.user_id.unwrap_or(Uuid::nil())).await {
+        Ok((job, true)) => (StatusCode::ACCEPTED, Json(ReturnType::ReturnExport(job.into()))),
+        Ok((job, false)) => (StatusCode::OK, Json(ReturnType::ReturnExport(job.into()))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("export#0x01 Internal server error")))),
+    }
+}
+
+/// Returns the status of an export of the user, with the download link once it is ready.
+pub async fn get_export(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(job_id): Path<Uuid>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    match ExportJob::from_job_id(&session, job_id).await {
+        Ok(job) if Some(job.user_id) == user.user_id => (StatusCode::OK, Json(ReturnType::ReturnExport(job.into()))),
+        _ => (StatusCode::NOT_FOUND, Json(ReturnType::Error(RequestError::from("Export not found")))),
+    }
+}
+
+/// Downloads an export archive. The link works once, the archive is removed afterwards.
+pub async fn download_export(
+    State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
+    Path(token): Path<Uuid>,
+) -> Response {
+    match ExportJob::consume(&session, &storage, token).await {
+        Ok(archive) => (
+            [
+                (header::CONTENT_TYPE, "application/zip"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"joltamp-export.zip\""),
+                (header::CACHE_CONTROL, "no-store"),
+            ],
+            archive,
+        ).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, Json(RequestError::from("Invalid download link"))).into_response(),
+    }
+}
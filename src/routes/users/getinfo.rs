@@ -1,32 +1,56 @@
-use crate::types::types::RequestError;
+use crate::security::auth::authorize_optional;
+use crate::types::annotation::Annotation;
+use crate::types::badge::{expand_badges, Badge};
+use crate::types::color::HexColor;
+use crate::types::friend::mutual_friends;
+use crate::types::privacy::Relation;
+use crate::types::token::Scope;
+use crate::types::types::{ProfileQuery, RequestError};
 use crate::types::user::{User, UserFunc};
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
 use scylla::Session;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::sync::Arc;
 use chrono::NaiveDate;
 use uuid::Uuid;
 
-#[derive(Deserialize)]
-pub struct RequestUser {
-
+#[derive(Serialize)]
+pub struct UserInfo {
+    createdat: String,
+    user_id: Option<Uuid>,
+    username: Option<String>,
+    displayname: Option<String>,
+    badges: Option<Vec<Uuid>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    badge_info: Option<Vec<Badge>>,
+    status: Option<i8>,
+    bannercolor: Option<HexColor>,
+    backgroundcolor: Option<HexColor>,
+    desc: Option<String>,
+    pronouns: Option<String>,
+    links: Option<Vec<String>>,
+    avatar: Option<String>,
+    banner: Option<String>,
+    isbot: Option<bool>,
+    deleted: bool,
+    /// Whether the viewer may send a friend request, always false for anonymous viewers
+    can_send_friend_request: bool,
+    /// Whether the viewer may send a direct message, always false for anonymous viewers
+    can_send_message: bool,
+    /// Friends the viewer and the user have in common
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mutual_friends: Option<Vec<Uuid>>,
+    /// Private note of the viewer about the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
 }
 
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum ReturnType {
-    ReturnData{
-        createdat: String,
-        user_id: Option<Uuid>,
-        username: Option<String>,
-        displayname: Option<String>,
-        badges: Option<Vec<Uuid>>,
-        status: Option<i8>,
-        bannercolor: Option<String>,
-        backgroundcolor: Option<String>,
-    },
+    ReturnData(Box<UserInfo>),
     Error(RequestError),
 }
 
@@ -35,7 +59,9 @@ pub enum ReturnType {
 /// # Parameters
 ///
 /// * `State(session)`: An instance of `Arc<Session>` representing the database session.
+/// * `headers`: Optional `Authorization` header, the profile is tailored to the privacy settings towards the viewer.
 /// * `Path(user_id)`: A `Uuid` representing the user ID for which to retrieve information.
+/// * `Query(query)`: With `?expand=badges` the badge ids are also returned as full badge objects.
 ///
 /// # Return
 ///
@@ -47,25 +73,66 @@ pub enum ReturnType {
 /// * `ReturnType::Error`: Contains an error message indicating the incorrect user ID.
 pub async fn get_info(
     State(session): State<Arc<Session>>,
+    headers: HeaderMap,
     Path(user_id): Path<Uuid>,
+    Query(query): Query<ProfileQuery>,
 ) -> (StatusCode, Json<ReturnType>) {
+    let viewer = authorize_optional(&session, &headers, Scope::ReadProfile).await;
     let user = User::from_user_id(user_id).fill_info(&session).await;
-    // Check if the user is fetched from db
-    if let Ok(user) = user{
+    // Check if the user is fetched from db and visible to the viewer
+    let info = match user {
+        Ok(user) => user_info(&session, &query, viewer.as_ref(), user).await,
+        Err(_) => None,
+    };
+    if let Some(info) = info {
         // Returns data to user
-        (StatusCode::OK, Json(ReturnType::ReturnData {
-            createdat: user.createdat.unwrap_or(NaiveDate::MIN).format("%Y-%m-%d").to_string(),
-            user_id: user.user_id,
-            username: user.username,
-            displayname: user.displayname,
-            badges: user.badges,
-            status: user.status,
-            bannercolor: user.bannercolor,
-            backgroundcolor: user.backgroundcolor,
-        }))
+        (StatusCode::OK, Json(ReturnType::ReturnData(Box::new(info))))
     }else{
         // Bad request error for non existing user
         (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))))
     }
 
-}
\ No newline at end of file
+}
+
+/// Builds the public profile of a user as seen by `viewer`, applying the privacy settings of the user.
+///
+/// Returns `None` if the profile is hidden from the viewer: the account is deactivated
+/// or the user blocked the viewer.
+pub async fn user_info(session: &Arc<Session>, query: &ProfileQuery, viewer: Option<&User>, user: User) -> Option<UserInfo> {
+    let relation = Relation::between(viewer, &user);
+    if user.deactivated.unwrap_or(false) || relation == Relation::BlockedBy {
+        return None;
+    }
+    let privacy = user.privacy.unwrap_or_default();
+    let mutual = match (viewer.and_then(|viewer| viewer.relations.as_ref()), &user.relations) {
+        (Some(viewer_relations), Some(relations)) if relation != Relation::Myself => Some(mutual_friends(viewer_relations, relations)),
+        _ => None,
+    };
+    let note = match (viewer.and_then(|viewer| viewer.user_id), user.user_id) {
+        (Some(viewer_id), Some(user_id)) if viewer_id != user_id => Annotation::get(session, viewer_id, user_id).await
+            .ok().flatten().and_then(|annotation| annotation.note),
+        _ => None,
+    };
+    Some(UserInfo {
+        createdat: user.createdat.unwrap_or(NaiveDate::MIN).format("%Y-%m-%d").to_string(),
+        user_id: user.user_id,
+        username: user.username,
+        displayname: user.displayname,
+        badge_info: expand_badges(session, query, &user.badges).await,
+        badges: user.badges,
+        status: user.status.filter(|_| privacy.shows_presence(relation)),
+        bannercolor: user.bannercolor,
+        backgroundcolor: user.backgroundcolor,
+        desc: user.desc,
+        pronouns: user.pronouns,
+        links: user.links,
+        avatar: user.avatar,
+        banner: user.banner,
+        isbot: user.isbot,
+        deleted: user.deleted.unwrap_or(false),
+        can_send_friend_request: viewer.is_some() && privacy.allows_friend_request(relation),
+        can_send_message: viewer.is_some() && privacy.allows_direct_message(relation),
+        mutual_friends: mutual,
+        note,
+    })
+}
@@ -1,33 +1,46 @@
-use crate::types::types::RequestError;
-use crate::types::user::{User, UserFunc};
-use axum::extract::{State};
+use crate::security::auth::authorize;
+use crate::types::token::Scope;
+use crate::types::badge::{expand_badges, Badge};
+use crate::types::color::HexColor;
+use crate::types::privacy::PrivacySettings;
+use crate::types::types::{ProfileQuery, RequestError};
+use crate::types::user::User;
+use axum::extract::{Query, State};
 use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
 use scylla::Session;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::sync::Arc;
 use chrono::NaiveDate;
 use uuid::Uuid;
 
-#[derive(Deserialize)]
-pub struct RequestUser {
-
+#[derive(Serialize)]
+pub struct SelfInfo {
+    createdat: String,
+    user_id: Option<Uuid>,
+    username: Option<String>,
+    displayname: Option<String>,
+    badges: Option<Vec<Uuid>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    badge_info: Option<Vec<Badge>>,
+    status: Option<i8>,
+    bannercolor: Option<HexColor>,
+    backgroundcolor: Option<HexColor>,
+    desc: Option<String>,
+    pronouns: Option<String>,
+    links: Option<Vec<String>>,
+    avatar: Option<String>,
+    banner: Option<String>,
+    email: Option<String>,
+    isbot: Option<bool>,
+    owner_id: Option<Uuid>,
+    privacy: PrivacySettings,
 }
 
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum ReturnType {
-    ReturnData{
-        createdat: String,
-        user_id: Option<Uuid>,
-        username: Option<String>,
-        displayname: Option<String>,
-        badges: Option<Vec<Uuid>>,
-        status: Option<i8>,
-        bannercolor: Option<String>,
-        backgroundcolor: Option<String>,
-        email: Option<String>,
-    },
+    ReturnData(Box<SelfInfo>),
     Error(RequestError),
 }
 
@@ -36,6 +49,7 @@ pub enum ReturnType {
 /// # Parameters
 /// - `State(session)`: An `Arc` wrapped `Session` object used to interact with the database.
 /// - `headers`: A `HeaderMap` containing the HTTP headers, which should include the "Authorization" header with the user's JWT.
+/// - `Query(query)`: With `?expand=badges` the badge ids are also returned as full badge objects.
 ///
 /// # Returns
 /// A tuple containing:
@@ -44,30 +58,35 @@ pub enum ReturnType {
 pub async fn get_self_info(
     State(session): State<Arc<Session>>,
     headers: HeaderMap,
+    Query(query): Query<ProfileQuery>,
 ) -> (StatusCode, Json<ReturnType>) {
-    // Check if Authorization header is present
-    if let Some(auth) = headers.get("Authorization") {
-        let user = User::from_user_jwt(Uuid::parse_str(auth.to_str().unwrap_or("")).unwrap_or(Uuid::nil())).fill_info(&session).await;
-        // Check if the user is fetched from db
-        if let Ok(user) = user{
-            // Returns data to user
-            (StatusCode::OK, Json(ReturnType::ReturnData {
-                createdat: user.createdat.unwrap_or(NaiveDate::MIN).format("%Y-%m-%d").to_string(),
-                user_id: user.user_id,
-                username: user.username,
-                displayname: user.displayname,
-                badges: user.badges,
-                status: user.status,
-                bannercolor: user.bannercolor,
-                backgroundcolor: user.backgroundcolor,
-                email: user.email,
-            }))
-        }else{
-            // Bad request error for non existing user
-            (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("User JWT not found"))))
-        }   
-    }else {
-        // Bad request error for missing Authorization header
-        (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Invalid JWT"))))
-    }
-}
\ No newline at end of file
+    let user = match authorize(&session, &headers, Scope::ReadProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    (StatusCode::OK, Json(self_info(&session, &query, user).await))
+}
+
+/// Builds the self information response of an authenticated user
+pub async fn self_info(session: &Arc<Session>, query: &ProfileQuery, user: User) -> ReturnType {
+    ReturnType::ReturnData(Box::new(SelfInfo {
+        createdat: user.createdat.unwrap_or(NaiveDate::MIN).format("%Y-%m-%d").to_string(),
+        user_id: user.user_id,
+        username: user.username,
+        displayname: user.displayname,
+        badge_info: expand_badges(session, query, &user.badges).await,
+        badges: user.badges,
+        status: user.status,
+        bannercolor: user.bannercolor,
+        backgroundcolor: user.backgroundcolor,
+        desc: user.desc,
+        pronouns: user.pronouns,
+        links: user.links,
+        avatar: user.avatar,
+        banner: user.banner,
+        email: user.email,
+        isbot: user.isbot,
+        owner_id: user.owner_id,
+        privacy: user.privacy.unwrap_or_default(),
+    }))
+}
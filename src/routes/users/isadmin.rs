@@ -1,18 +1,16 @@
+use crate::security::auth::authorize_optional;
+use crate::types::privacy::Relation;
+use crate::types::token::Scope;
 use crate::types::types::RequestError;
 use crate::types::user::{User, UserFunc};
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
 use scylla::Session;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-#[derive(Deserialize)]
-pub struct RequestUser {
-
-}
-
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum ReturnType {
@@ -35,16 +33,19 @@ pub enum ReturnType {
 ///   The `Json` object contains `ReturnType::ReturnData(Some(true))` if the user is an admin,
 ///   or `ReturnType::ReturnData(Some(false))` if the user is not an admin.
 ///
-/// * `StatusCode::BAD_REQUEST`: If the user with the given `user_id` does not exist in the database.
+/// * `StatusCode::BAD_REQUEST`: If the user with the given `user_id` does not exist in the database
+///   or is hidden from the viewer.
 ///   The `Json` object contains `ReturnType::Error(RequestError::from("Incorrect userId"))`.
 pub async fn is_admin(
     State(session): State<Arc<Session>>,
+    headers: HeaderMap,
     Path(user_id): Path<Uuid>,
 ) -> (StatusCode, Json<ReturnType>) {
+    let viewer = authorize_optional(&session, &headers, Scope::ReadProfile).await;
     // Fetch user from db based on provided user_id
     let user = User::from_user_id(user_id).fill_info(&session).await;
-    // Check if the user is fetched from db
-    if let Ok(user) = user{
+    // Check if the user is fetched from db, users who blocked the viewer or are deactivated are hidden
+    if let Some(user) = user.ok().filter(|user| !user.deactivated.unwrap_or(false) && Relation::between(viewer.as_ref(), user) != Relation::BlockedBy){
         // Returns data to user
         (StatusCode::OK, Json(ReturnType::ReturnData(user.isadmin)))
     }else{
@@ -1,11 +1,15 @@
 use std::sync::Arc;
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::Json;
+use axum::{Extension, Json};
+use chrono::Utc;
 use scylla::Session;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::security::auth::check_suspension;
 use crate::security::passwords::verify_password;
+use crate::storage::Storage;
+use crate::types::deletion::AccountDeletion;
 use crate::types::types::{RequestError};
 use crate::types::user::{User, UserFunc};
 
@@ -38,20 +42,43 @@ pub enum ReturnType {
 /// * `StatusCode::OK`: If the user is successfully authenticated.
 /// * `ReturnType::ReturnUser`: Contains the user's ID and JWT if authentication is successful.
 /// * `StatusCode::UNAUTHORIZED`: If the user's email or password is invalid.
+/// * `StatusCode::FORBIDDEN`: If the account is suspended, the error carries the `account_suspended` code.
+/// * Logging in while a deletion of the account is pending cancels the deletion.
 /// * `ReturnType::Error`: Contains an error message if authentication fails.
 pub async fn login(
     State(session): State<Arc<Session>>,
+    Extension(storage): Extension<Arc<dyn Storage>>,
     Json(payload): Json<RequestUser>,
 ) -> (StatusCode, Json<ReturnType>) {
     
     // Fetch user from db based on provided email
-    let user: User = User::from_user_email(payload.email).fill_info(&session).await.unwrap();
+    let Ok(user) = User::from_user_email(payload.email).fill_info(&session).await else {
+        return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("Invalid email"))));
+    };
     
     // Check if password was successfully fetched
-    if let Some(password) = user.password {
+    if let Some(password) = &user.password {
         
         // Verify password
-        if verify_password(&payload.password, &password).is_ok() {
+        if verify_password(&payload.password, password).is_ok() {
+            // Suspended users are refused only after proving the password, so the reason is not leaked
+            if let Err((status, err)) = check_suspension(&session, &user).await {
+                return (status, Json(ReturnType::Error(err)));
+            }
+            // Logging in during the grace period cancels a pending deletion
+            match AccountDeletion::pending_for(&session, user.user_id.unwrap_or(Uuid::nil())).await {
+                Ok(None) => {}
+                Ok(Some(deletion)) if deletion.purgeat > Utc::now() => {
+                    if AccountDeletion::cancel(&session, &user).await.is_err() {
+                        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("login#0x02 Internal server error"))));
+                    }
+                }
+                Ok(Some(deletion)) => {
+                    let _ = deletion.purge(&session, &storage).await;
+                    return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("Invalid email"))));
+                }
+                Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("login#0x01 Internal server error")))),
+            }
             return (StatusCode::OK, Json(ReturnType::ReturnUser{ jwt: user.jwt.unwrap_or(Uuid::nil()), user_id: user.user_id.unwrap_or(Uuid::nil()), }));
         } else {
             // Failure while authorization
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use futures::StreamExt;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::routes::users::getinfo::{user_info, UserInfo};
+use crate::security::auth::authorize_optional;
+use crate::types::token::Scope;
+use crate::types::types::{ProfileQuery, RequestError};
+use crate::types::user::{User, UserFunc};
+
+const MAX_LOOKUP: usize = 100;
+// Database reads running at the same time for one lookup
+const MAX_CONCURRENT_READS: usize = 16;
+
+#[derive(Deserialize)]
+pub struct RequestUsers {
+    ids: Vec<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnUsers{
+        users: Vec<UserInfo>,
+        missing: Vec<Uuid>,
+    },
+    Error(RequestError),
+}
+
+/// Resolves the public profiles of several users at once, shaped like `getInfo`.
+///
+/// Duplicate ids are resolved once, profiles hidden from the viewer are reported as missing. Supports `?expand=badges` like `getInfo`.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the found profiles in request order and the ids that do not exist in `missing`.
+/// * `StatusCode::BAD_REQUEST`: If more than 100 ids are requested.
+pub async fn lookup_users(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Query(query): Query<ProfileQuery>,
+    Json(payload): Json<RequestUsers>,
+) -> (StatusCode, Json<ReturnType>) {
+    if payload.ids.len() > MAX_LOOKUP {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(format!("Too many ids (>{})", MAX_LOOKUP)))));
+    }
+    let viewer = authorize_optional(&session, &headers, Scope::ReadProfile).await;
+    let mut seen = HashSet::new();
+    let ids: Vec<Uuid> = payload.ids.into_iter().filter(|id| seen.insert(*id)).collect();
+
+    let results: Vec<(Uuid, Option<UserInfo>)> = futures::stream::iter(ids)
+        .map(|user_id| {
+            let session = &session;
+            let query = &query;
+            let viewer = viewer.as_ref();
+            async move {
+                match User::from_user_id(user_id).fill_info(session).await {
+                    Ok(user) => (user_id, user_info(session, query, viewer, user).await),
+                    Err(_) => (user_id, None),
+                }
+            }
+        })
+        .buffered(MAX_CONCURRENT_READS)
+        .collect().await;
+
+    let mut users = Vec::new();
+    let mut missing = Vec::new();
+    for (user_id, user) in results {
+        match user {
+            Some(user) => users.push(user),
+            None => missing.push(user_id),
+        }
+    }
+    (StatusCode::OK, Json(ReturnType::ReturnUsers { users, missing }))
+}
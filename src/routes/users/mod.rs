@@ -4,4 +4,15 @@ pub mod getinfo;
 pub mod getselfinfo;
 pub mod isadmin;
 pub mod setstatus;
-pub mod changeselfinfo;
\ No newline at end of file
+pub mod changeselfinfo;
+pub mod reauth;
+pub mod resetpassword;
+pub mod patchself;
+pub mod changeusername;
+pub mod search;
+pub mod lookup;
+pub mod deleteself;
+pub mod export;
+pub mod privacy;
+pub mod settings;
+pub mod note;
\ No newline at end of file
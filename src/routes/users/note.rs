@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::functions::validation::normalize_note;
+use crate::security::auth::authorize;
+use crate::types::annotation::Annotation;
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+#[derive(Deserialize)]
+pub struct RequestNote {
+    note: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnNote{
+        user_id: Uuid,
+        note: Option<String>,
+    },
+    Error(RequestError),
+}
+
+/// Sets a private note about any user, returned only to its author on profile lookups.
+///
+/// A missing or empty `note` clears it.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the stored note.
+/// * `StatusCode::BAD_REQUEST`: If the user does not exist or the note is too long.
+pub async fn set_note(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Path(target_id): Path<Uuid>,
+    Json(payload): Json<RequestNote>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageFriends).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+    if target_id == user_id || User::from_user_id(target_id).fill_info(&session).await.is_err() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    }
+    let note = match payload.note.as_deref().map(normalize_note) {
+        Some(Ok(note)) if !note.is_empty() => Some(note),
+        Some(Err(err)) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+        _ => None,
+    };
+
+    match Annotation::set_note(&session, user_id, target_id, note.clone()).await {
+        Ok(()) => (StatusCode::OK, Json(ReturnType::ReturnNote { user_id: target_id, note })),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("note#0x01 Internal server error")))),
+    }
+}
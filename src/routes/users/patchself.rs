@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::Serialize;
+use crate::routes::users::getselfinfo::{self_info, ReturnType};
+use crate::security::auth::authorize;
+use crate::types::profile::{FieldError, ProfilePatch};
+use crate::types::token::Scope;
+use crate::types::types::{ProfileQuery, RequestError};
+use crate::types::user::UserFunc;
+
+#[derive(Serialize)]
+struct ValidationDetails {
+    fields: Vec<FieldError>,
+}
+
+/// Updates several profile fields at once.
+///
+/// Every field present in the body is validated first, nothing is written if any of them
+/// is invalid. Valid changes are applied in a single statement.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the updated profile, shaped like `getSelfInfo`.
+/// * `StatusCode::BAD_REQUEST`: With the `validation_failed` code and a `fields` list of per-field errors.
+pub async fn patch_self(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Query(query): Query<ProfileQuery>,
+    Json(payload): Json<ProfilePatch>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::WriteProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let changes = match payload.into_changes() {
+        Ok(changes) => changes,
+        Err(fields) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(
+            RequestError::from("Validation failed").with_code("validation_failed", ValidationDetails { fields })
+        ))),
+    };
+
+    match user.update_fields(&session, changes).await {
+        Ok(user) => (StatusCode::OK, Json(self_info(&session, &query, user).await)),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from(err.to_string())))),
+    }
+}
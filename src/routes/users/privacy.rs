@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use crate::security::auth::authorize;
+use crate::types::privacy::{DirectMessages, FriendRequests, PrivacySettings};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+/// Partial update of the privacy settings, fields that are not present stay unchanged.
+#[derive(Deserialize)]
+pub struct RequestPrivacy {
+    #[serde(rename = "friendRequests")]
+    friend_requests: Option<FriendRequests>,
+    #[serde(rename = "directMessages")]
+    direct_messages: Option<DirectMessages>,
+    #[serde(rename = "presenceVisible")]
+    presence_visible: Option<bool>,
+    discoverable: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnPrivacy(PrivacySettings),
+    Error(RequestError),
+}
+
+/// Updates the privacy settings of the authenticated user.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the complete settings after the update.
+pub async fn update_privacy(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestPrivacy>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::WriteProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let mut privacy = user.privacy.unwrap_or_default();
+    privacy.friend_requests = payload.friend_requests.unwrap_or(privacy.friend_requests);
+    privacy.direct_messages = payload.direct_messages.unwrap_or(privacy.direct_messages);
+    privacy.presence_visible = payload.presence_visible.unwrap_or(privacy.presence_visible);
+    privacy.discoverable = payload.discoverable.unwrap_or(privacy.discoverable);
+
+    match privacy.save(&session, &user).await {
+        Ok(()) => (StatusCode::OK, Json(ReturnType::ReturnPrivacy(privacy))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("privacy#0x01 Internal server error")))),
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::security::auth::authorize;
+use crate::security::passwords::verify_password;
+use crate::security::reauth::create_reauth_token;
+use crate::types::token::Scope;
+use crate::types::types::{RequestError};
+
+#[derive(Deserialize)]
+pub struct RequestUser {
+    password: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnToken{
+        reauth_token: Uuid,
+    },
+    Error(RequestError),
+}
+
+/// Re-authenticates the user with their current password and issues a short-lived re-auth token.
+///
+/// The token can be sent as `reauthToken` instead of `currentPassword` when changing sensitive fields.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With `ReturnType::ReturnToken` if the password is correct.
+/// * `StatusCode::UNAUTHORIZED`: If the JWT or the password is invalid.
+pub async fn reauth(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestUser>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ManageAccount).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if verify_password(&payload.password, user.password.as_deref().unwrap_or("")).is_err() {
+        return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("Invalid password"))));
+    }
+    if let Ok(token) = create_reauth_token(&session, user.user_id.unwrap_or(Uuid::nil())).await {
+        (StatusCode::OK, Json(ReturnType::ReturnToken { reauth_token: token }))
+    }else{
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("reauth#0x01 Internal server error"))))
+    }
+}
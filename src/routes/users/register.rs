@@ -6,8 +6,12 @@ use scylla::Session;
 use serde::{Deserialize, Serialize};
 use serde::de::StdError;
 use uuid::Uuid;
+use crate::functions::validation::normalize_username;
 use crate::security::passwords::hash_password;
+use crate::security::policy::{check_password, PasswordPolicyError};
 use crate::types::types::{RequestError};
+use crate::types::search::SearchIndex;
+use crate::types::username::{UsernameChange, UsernameClaim};
 
 #[derive(Deserialize)]
 pub struct RequestUser {
@@ -23,6 +27,7 @@ pub enum ReturnType {
         user_id: Uuid,
         jwt: Uuid,
     },
+    PasswordRejected(PasswordPolicyError),
     Error(RequestError),
 }
 
@@ -36,8 +41,14 @@ pub async fn register(
     if !payload.email.contains('@'){
         return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Invalid e-mail address"))));
     }
-    if payload.password.len() < 3 || payload.username.len() < 3{
-        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Password or Username is too short (<4)"))));
+    // The username doubles as the initial display name, which follows the same rules
+    payload.username = match normalize_username(&payload.username) {
+        Ok(username) => username,
+        Err(err) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err)))),
+    };
+    let displayname = payload.username.clone();
+    if let Err(err) = check_password(&payload.password, &payload.username, &payload.email) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::PasswordRejected(err)));
     }
 
     if let Ok(used) = check_username_free(&session, &payload.username, &payload.email).await {
@@ -47,10 +58,17 @@ pub async fn register(
     }else{
         return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("register#0x01 Internal server error"))));
     }
-    if let Ok(user) = insert_user(&session, &mut payload).await{
+    // The check above reads the users table, the claim settles concurrent registrations
+    let user_id = Uuid::new_v4();
+    match UsernameClaim::claim(&session, &payload.username, user_id).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Username/Email already used")))),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("register#0x03 Internal server error")))),
+    }
+    if let Ok(jwt) = insert_user(&session, &mut payload, user_id, &displayname).await{
         (StatusCode::CREATED, Json(ReturnType::ReturnUser{
-            jwt: user.0,
-            user_id: user.1,
+            jwt,
+            user_id,
         }))
     }else{
         (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("register#0x02 Internal server error"))))
@@ -60,25 +78,44 @@ pub async fn register(
 
 /// Checks if username is already used in database
 /// 
-/// Returns Ok(true) if username is already used
+/// Returns Ok(true) if username is already used or still reserved after a username change
 /// 
 /// Returns Ok(false if username is free to use
-async fn check_username_free(session: &Arc<Session>, username: &String, email: &String) -> Result<bool, Box<dyn StdError>> {
-    let result = session.query_unpaged("SELECT user_id FROM joltamp.users WHERE username = ? ALLOW FILTERING", (username, )).await?.into_rows_result()?;
+pub async fn check_username_free(session: &Arc<Session>, username: &String, email: &String) -> Result<bool, Box<dyn StdError>> {
+    let used = username_owner(session, username).await?.is_some();
+    // Bots are created without an e-mail, so only the username is checked for them
+    if email.is_empty() {
+        return Ok(used);
+    }
     let result2 = session.query_unpaged("SELECT user_id FROM joltamp.users WHERE email = ? ALLOW FILTERING", (email, )).await?.into_rows_result()?;
 
-    Ok(result.rows_num() != 0 || result2.rows_num() != 0)
+    Ok(used || result2.rows_num() != 0)
+}
+
+/// Returns the id of the user holding the username, either as current username
+/// or as a reserved previous one
+pub async fn username_owner(session: &Arc<Session>, username: &String) -> Result<Option<Uuid>, Box<dyn StdError>> {
+    let result = session.query_unpaged("SELECT user_id FROM joltamp.users WHERE username = ? ALLOW FILTERING", (username, )).await?.into_rows_result()?;
+    if let Some((user_id, )) = result.maybe_first_row::<(Uuid, )>()? {
+        return Ok(Some(user_id));
+    }
+    Ok(UsernameChange::reserved_by(session, username).await?)
 }
 
 /// Pushes user to database
-/// and returns generated jwt and user_id from
+/// and returns generated jwt from
 /// function to return it to end user
-async fn insert_user(session: &Arc<Session>, payload: &mut RequestUser) -> Result<(Uuid, Uuid), Box<dyn StdError>> {
+async fn insert_user(session: &Arc<Session>, payload: &mut RequestUser, gen_user_id: Uuid, displayname: &str) -> Result<Uuid, Box<dyn StdError>> {
     let gen_jwt = Uuid::new_v4();
-    let gen_user_id = Uuid::new_v4();
     payload.password = hash_password(&mut payload.password).unwrap();
-    session.query_unpaged("INSERT INTO joltamp.users (createdat, user_id, username, displayname, email, password, isadmin, jwt, status) VALUES (todate(now()), ?, ?, ?, ?, ?, false, ?, 0)",
-                                     (gen_user_id, &payload.username, &payload.username, &payload.email, &payload.password, gen_jwt)
-    ).await?;
-    Ok((gen_jwt, gen_user_id))
+    let res = session.query_unpaged("INSERT INTO joltamp.users (createdat, user_id, username, displayname, email, password, isadmin, jwt, status) VALUES (todate(now()), ?, ?, ?, ?, ?, false, ?, 0)",
+                                     (gen_user_id, &payload.username, displayname, &payload.email, &payload.password, gen_jwt)
+    ).await;
+    // The claimed username is given back if the user was not created
+    if let Err(err) = res {
+        let _ = UsernameClaim::release(session, &payload.username, gen_user_id).await;
+        return Err(err.into());
+    }
+    SearchIndex::index(session, gen_user_id, &payload.username, displayname).await?;
+    Ok(gen_jwt)
 }
\ No newline at end of file
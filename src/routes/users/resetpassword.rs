@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::notifier::Notifier;
+use crate::security::policy::{check_password, PasswordPolicyError};
+use crate::security::reauth::record_security_event;
+use crate::security::reset::{consume_reset_token, reset_token_owner, restore_reset_token};
+use crate::types::types::{RequestError};
+use crate::types::user::{User, UserFunc};
+
+#[derive(Deserialize)]
+pub struct RequestUser {
+    token: Uuid,
+    #[serde(rename = "newPassword")]
+    new_password: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    Ok,
+    PasswordRejected(PasswordPolicyError),
+    Error(RequestError),
+}
+
+/// Sets a new password using a reset token issued by an admin.
+///
+/// The new password has to satisfy the password policy. The token is only consumed once
+/// the new password is accepted, so a rejected password can be retried with the same token.
+pub async fn reset_password(
+    State(session): State<Arc<Session>>,
+    Extension(notifier): Extension<Arc<dyn Notifier>>,
+    Json(payload): Json<RequestUser>,
+) -> (StatusCode, Json<ReturnType>) {
+    let Ok(user_id) = reset_token_owner(&session, payload.token).await else {
+        return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("Invalid reset token"))));
+    };
+    let Ok(user) = User::from_user_id(user_id).fill_info(&session).await else {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Incorrect userId"))));
+    };
+    let email = user.email.clone().unwrap_or_default();
+    if let Err(policy_err) = check_password(&payload.new_password, user.username.as_deref().unwrap_or(""), &email) {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::PasswordRejected(policy_err)));
+    }
+
+    // Only one request can consume the token, a concurrent one gets rejected here
+    match consume_reset_token(&session, payload.token).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("Invalid reset token")))),
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("resetPassword#0x01 Internal server error")))),
+    }
+    match user.update(&session, "password", payload.new_password).await {
+        Ok(_) => {
+            let _ = record_security_event(&session, &notifier, user_id, &email, "password_reset").await;
+            (StatusCode::OK, Json(ReturnType::Ok))
+        }
+        Err(err) => {
+            // The account has no usable password without the token, hand it back
+            if let Err(restore_err) = restore_reset_token(&session, payload.token, user_id).await {
+                tracing::error!("Cannot restore reset token of {}: {:?}", user_id, restore_err);
+            }
+            match err.downcast::<PasswordPolicyError>() {
+                Ok(policy_err) => (StatusCode::BAD_REQUEST, Json(ReturnType::PasswordRejected(policy_err))),
+                Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("resetPassword#0x02 Internal server error")))),
+            }
+        }
+    }
+}
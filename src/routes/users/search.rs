@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use futures::StreamExt;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::functions::validation::search_key;
+use crate::security::auth::authorize;
+use crate::types::friend::Friend;
+use crate::types::privacy::Relation;
+use crate::types::search::SearchIndex;
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+const DEFAULT_LIMIT: usize = 25;
+const MAX_LIMIT: usize = 50;
+// Index entries checked per request at most, bounds the work of a single search
+const MAX_CANDIDATES: i32 = 200;
+// Database reads running at the same time for one request
+const MAX_CONCURRENT_READS: usize = 16;
+
+#[derive(Deserialize)]
+pub struct RequestSearch {
+    query: String,
+    after: Option<Uuid>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    user: Friend,
+    isfriend: bool,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnResults{
+        results: Vec<SearchResult>,
+        next_after: Option<Uuid>,
+    },
+    Error(RequestError),
+}
+
+/// Searches users by the start of their username, display name or a word of their display name,
+/// ignoring case and accents.
+///
+/// Users who blocked the caller are never returned, users who turned off `discoverable`
+/// are only returned to their friends and the status follows the presence setting.
+/// Results are shaped like friends, `friendstatus` is the caller's relation to the user.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With at most `limit` results (default 25, max 50) and the `after` cursor of the
+///   next page, if any. A page can hold fewer results than `limit` while more pages follow.
+/// * `StatusCode::BAD_REQUEST`: If the query is empty.
+pub async fn search(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestSearch>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ReadProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let query = search_key(payload.query.trim());
+    if query.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Not every field satisfied"))));
+    }
+    let limit = payload.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    match find_users(&session, &user, &query, payload.after, limit).await {
+        Ok((results, next_after)) => (StatusCode::OK, Json(ReturnType::ReturnResults { results, next_after })),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("search#0x01 Internal server error")))),
+    }
+}
+
+// Checks the users indexed under the prefix of the query in id order, until the page is full
+// or the candidates of this request are used up
+async fn find_users(session: &Arc<Session>, viewer: &User, query: &str, after: Option<Uuid>, limit: usize)
+    -> anyhow::Result<(Vec<SearchResult>, Option<Uuid>)> {
+    let candidates = SearchIndex::candidates(session, query, after, MAX_CANDIDATES).await?;
+    let more_candidates = candidates.len() == MAX_CANDIDATES as usize;
+    let last_candidate = candidates.last().copied();
+
+    let mut results = Vec::new();
+    let mut checked = futures::stream::iter(candidates)
+        .map(|candidate| async move { (candidate, User::from_user_id(candidate).fill_info(session).await) })
+        .buffered(MAX_CONCURRENT_READS);
+    while let Some((candidate_id, candidate)) = checked.next().await {
+        let Ok(candidate) = candidate else { continue };
+        let relation = Relation::between(Some(viewer), &candidate);
+        let privacy = candidate.privacy.unwrap_or_default();
+        if candidate.deactivated.unwrap_or(false) || candidate.deleted.unwrap_or(false) || !privacy.discoverable_by(relation) {
+            continue;
+        }
+        // Queries longer than the indexed prefix are matched on the names themselves
+        if !matches_names(query, candidate.username.as_deref().unwrap_or(""), candidate.displayname.as_deref().unwrap_or("")) {
+            continue;
+        }
+        let friendstatus = viewer.relations.as_ref().and_then(|relations| relations.get(&candidate_id)).copied().unwrap_or(0) as u8;
+        let mut user = Friend::for_viewer(candidate, relation);
+        user.friendstatus = friendstatus;
+        results.push(SearchResult { user, isfriend: relation == Relation::Friend });
+        if results.len() == limit {
+            return Ok((results, Some(candidate_id)));
+        }
+    }
+    Ok((results, last_candidate.filter(|_| more_candidates)))
+}
+
+fn matches_names(query: &str, username: &str, displayname: &str) -> bool {
+    let displayname = search_key(displayname);
+    search_key(username).starts_with(query)
+        || displayname.starts_with(query)
+        || displayname.split_whitespace().any(|word| word.starts_with(query))
+}
@@ -4,9 +4,10 @@ use axum::http::{HeaderMap, StatusCode};
 use axum::Json;
 use scylla::Session;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use crate::security::auth::authorize;
+use crate::types::token::Scope;
 use crate::types::types::{RequestError};
-use crate::types::user::{User, UserFunc};
+use crate::types::user::UserFunc;
 
 #[derive(Deserialize, Debug)]
 pub struct RequestUser {
@@ -24,22 +25,14 @@ pub async fn set_status(
     headers: HeaderMap,
     Json(payload): Json<RequestUser>,
 ) -> (StatusCode, Json<ReturnType>) {
-    
-    if let Some(auth ) = headers.get("Authorization") {
-        // Fetch user from db based on provided jwt
-        let user = User::from_user_jwt(Uuid::parse_str(auth.to_str().unwrap_or("")).unwrap_or(Uuid::nil())).fill_info(&session).await;
-        if let Ok(user) = user{
-            let res = user.update(&session, "status", payload.status.to_string()).await;
-            if let Err(err) = &res {
-                (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string()))))
-            }else{
-                (StatusCode::OK, Json(ReturnType::Ok))
-            }
-        }else{
-            (StatusCode::UNAUTHORIZED, Json(ReturnType::Error(RequestError::from("User JWT not found"))))
-        }
+    let user = match authorize(&session, &headers, Scope::WriteProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    let res = user.update(&session, "status", payload.status.to_string()).await;
+    if let Err(err) = &res {
+        (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err.to_string()))))
     }else{
-        (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Invalid JWT"))))
+        (StatusCode::OK, Json(ReturnType::Ok))
     }
-
 }
\ No newline at end of file
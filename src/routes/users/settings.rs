@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::gateway::Gateway;
+use crate::security::auth::authorize;
+use crate::types::settings::{ClientSettings, SettingsDocument};
+use crate::types::token::Scope;
+use crate::types::types::RequestError;
+
+#[derive(Deserialize)]
+pub struct RequestSettings {
+    /// Version the client based its changes on
+    version: i32,
+    settings: ClientSettings,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ReturnType {
+    ReturnSettings(SettingsDocument),
+    Error(RequestError),
+}
+
+/// Returns the synced client settings of the authenticated user with their version.
+pub async fn get_settings(
+    State(session): State<Arc<Session>>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::ReadProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    match SettingsDocument::for_user(&session, user.user_id.unwrap_or(Uuid::nil())).await {
+        Ok(document) => (StatusCode::OK, Json(ReturnType::ReturnSettings(document))),
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("settings#0x01 Internal server error")))),
+    }
+}
+
+/// Replaces the synced client settings of the authenticated user.
+///
+/// The write only succeeds if `version` matches the stored version, other connected clients
+/// of the user receive the new document as a `SETTINGS_UPDATE` gateway event.
+///
+/// # Returns
+///
+/// * `StatusCode::OK`: With the stored document and its new version.
+/// * `StatusCode::BAD_REQUEST`: If the settings exceed the limits.
+/// * `StatusCode::CONFLICT`: With the `version_conflict` code and the `current` document, the client has to merge and retry.
+pub async fn put_settings(
+    State(session): State<Arc<Session>>,
+    Extension(gateway): Extension<Gateway>,
+    headers: HeaderMap,
+    Json(payload): Json<RequestSettings>,
+) -> (StatusCode, Json<ReturnType>) {
+    let user = match authorize(&session, &headers, Scope::WriteProfile).await {
+        Ok(user) => user,
+        Err((status, err)) => return (status, Json(ReturnType::Error(err))),
+    };
+    if let Err(err) = payload.settings.validate() {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from(err))));
+    }
+    if payload.version < 0 || payload.version == i32::MAX {
+        return (StatusCode::BAD_REQUEST, Json(ReturnType::Error(RequestError::from("Invalid version"))));
+    }
+    let user_id = user.user_id.unwrap_or(Uuid::nil());
+
+    match SettingsDocument::replace(&session, user_id, payload.version, payload.settings).await {
+        Ok(Some(document)) => {
+            gateway.publish(user_id, "SETTINGS_UPDATE", &document);
+            (StatusCode::OK, Json(ReturnType::ReturnSettings(document)))
+        }
+        Ok(None) => {
+            #[derive(Serialize)]
+            struct Details {
+                current: Option<SettingsDocument>,
+            }
+            let current = SettingsDocument::for_user(&session, user_id).await.ok();
+            let err = RequestError::from("Settings were changed by another client")
+                .with_code("version_conflict", Details { current });
+            (StatusCode::CONFLICT, Json(ReturnType::Error(err)))
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ReturnType::Error(RequestError::from("settings#0x02 Internal server error")))),
+    }
+}
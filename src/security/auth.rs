@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use axum::http::{HeaderMap, StatusCode};
+use scylla::Session;
+use uuid::Uuid;
+use crate::types::suspension::Suspension;
+use crate::types::token::{ApiToken, Scope};
+use crate::types::types::RequestError;
+use crate::types::user::{User, UserFunc};
+
+/// Authenticates a request based on its `Authorization` header and fetches the user.
+///
+/// # Accepted formats
+/// - `<jwt>`: User session, holds every scope.
+/// - `Bot <token>`: API token of a bot account.
+/// - `Bearer <token>`: Personal API token of a user.
+///
+/// # Returns
+/// The filled user object, or a status code with an error message when the header is missing,
+/// the credential is unknown, the token does not hold the required `scope` or the account is suspended.
+/// Bot tokens are also refused while the owner of the bot is suspended, deactivated or deleted.
+pub async fn authorize(session: &Arc<Session>, headers: &HeaderMap, scope: Scope) -> Result<User, (StatusCode, RequestError)> {
+    let user = authenticate(session, headers, scope).await?;
+    check_suspension(session, &user).await?;
+    Ok(user)
+}
+
+/// Authenticates the request like `authorize` if it carries an `Authorization` header.
+///
+/// Used by public endpoints that tailor the response to the viewer, invalid credentials
+/// are treated like an anonymous request.
+pub async fn authorize_optional(session: &Arc<Session>, headers: &HeaderMap, scope: Scope) -> Option<User> {
+    headers.get("Authorization")?;
+    authorize(session, headers, scope).await.ok()
+}
+
+/// Refuses suspended users with the suspension reason and end date
+pub async fn check_suspension(session: &Arc<Session>, user: &User) -> Result<(), (StatusCode, RequestError)> {
+    match Suspension::active_for(session, user.user_id.unwrap_or(Uuid::nil())).await {
+        Ok(None) => Ok(()),
+        Ok(Some(suspension)) => Err((StatusCode::FORBIDDEN, suspension.to_error())),
+        Err(_) => Err((StatusCode::INTERNAL_SERVER_ERROR, RequestError::from("auth#0x01 Internal server error"))),
+    }
+}
+
+async fn authenticate(session: &Arc<Session>, headers: &HeaderMap, scope: Scope) -> Result<User, (StatusCode, RequestError)> {
+    let Some(auth) = headers.get("Authorization") else {
+        return Err((StatusCode::BAD_REQUEST, RequestError::from("Invalid JWT")));
+    };
+    let auth = auth.to_str().unwrap_or("");
+
+    let (token, bot) = if let Some(token) = auth.strip_prefix("Bot ") {
+        (token, true)
+    } else if let Some(token) = auth.strip_prefix("Bearer ") {
+        (token, false)
+    } else {
+        // Plain JWT of a user session
+        return User::from_user_jwt(Uuid::parse_str(auth).unwrap_or(Uuid::nil())).fill_info(session).await
+            .map_err(|_| (StatusCode::UNAUTHORIZED, RequestError::from("User JWT not found")));
+    };
+
+    let Ok(api_token) = ApiToken::from_plain(session, token.trim()).await else {
+        return Err((StatusCode::UNAUTHORIZED, RequestError::from("Invalid API token")));
+    };
+    if !api_token.scopes.contains(&scope) {
+        return Err((StatusCode::FORBIDDEN, RequestError::from(format!("Missing scope: {}", scope.as_str()))));
+    }
+    let Ok(user) = User::from_user_id(api_token.user_id).fill_info(session).await else {
+        return Err((StatusCode::UNAUTHORIZED, RequestError::from("Invalid API token")));
+    };
+    // Bot tokens must be sent with the Bot prefix and user tokens without it
+    if user.isbot.unwrap_or(false) != bot {
+        return Err((StatusCode::UNAUTHORIZED, RequestError::from("Invalid API token")));
+    }
+    if bot {
+        check_bot_owner(session, &user).await?;
+    }
+    let _ = api_token.touch(session).await;
+    Ok(user)
+}
+
+/// Refuses bot tokens while the owner of the bot is suspended, deactivated or deleted
+async fn check_bot_owner(session: &Arc<Session>, bot: &User) -> Result<(), (StatusCode, RequestError)> {
+    let Some(owner_id) = bot.owner_id else {
+        return Err((StatusCode::UNAUTHORIZED, RequestError::from("Invalid API token")));
+    };
+    let Ok(owner) = User::from_user_id(owner_id).fill_info(session).await else {
+        return Err((StatusCode::UNAUTHORIZED, RequestError::from("Invalid API token")));
+    };
+    if owner.deactivated.unwrap_or(false) || owner.deleted.unwrap_or(false) {
+        return Err((StatusCode::FORBIDDEN, RequestError::from("Bot owner account is unavailable")));
+    }
+    check_suspension(session, &owner).await
+}
+
+/// Authenticates a request like `authorize` and additionally requires the user to be an admin.
+///
+/// Only user sessions are accepted, API tokens never grant admin access.
+pub async fn require_admin(session: &Arc<Session>, headers: &HeaderMap) -> Result<User, (StatusCode, RequestError)> {
+    let user = authorize(session, headers, Scope::ManageAccount).await?;
+    if !user.isadmin.unwrap_or(false) {
+        return Err((StatusCode::FORBIDDEN, RequestError::from("Admin only")));
+    }
+    Ok(user)
+}
@@ -1 +1,5 @@
-pub mod passwords;
\ No newline at end of file
+pub mod auth;
+pub mod passwords;
+pub mod policy;
+pub mod reauth;
+pub mod reset;
\ No newline at end of file
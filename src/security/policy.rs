@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MAX_PASSWORD_LENGTH: usize = 128;
+const MIN_ENTROPY_BITS: f64 = 40.0;
+
+// Bundled list of SHA-1 hashes of well known leaked passwords (HIBP format, one per line)
+const BUNDLED_COMPROMISED: &str = include_str!("../../assets/compromised_passwords.txt");
+
+static COMPROMISED: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Single reason why a password was rejected, sent to the client so it can display it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordIssue {
+    TooShort,
+    TooLong,
+    TooWeak,
+    ContainsUsername,
+    ContainsEmail,
+    Compromised,
+}
+
+/// Error returned when a password does not satisfy the password policy.
+#[derive(Serialize, Debug)]
+pub struct PasswordPolicyError {
+    error: String,
+    reasons: Vec<PasswordIssue>,
+}
+
+impl Display for PasswordPolicyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for PasswordPolicyError {}
+
+/// Checks a plain text password against the password policy.
+///
+/// # Parameters
+/// - `password`: The plain text password to check.
+/// - `username`: Username of the account, the password may not contain it.
+/// - `email`: E-mail of the account, the password may not contain it or its local part.
+///
+/// # Returns
+/// `Ok(())` if the password is accepted, or a `PasswordPolicyError` listing every reason
+/// the password was rejected.
+pub fn check_password(password: &str, username: &str, email: &str) -> Result<(), PasswordPolicyError> {
+    let mut reasons = Vec::new();
+    let length = password.chars().count();
+    let lowered = password.to_lowercase();
+
+    if length < MIN_PASSWORD_LENGTH {
+        reasons.push(PasswordIssue::TooShort);
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        reasons.push(PasswordIssue::TooLong);
+    }
+    if estimate_entropy(password) < MIN_ENTROPY_BITS {
+        reasons.push(PasswordIssue::TooWeak);
+    }
+    if username.chars().count() >= 3 && lowered.contains(&username.to_lowercase()) {
+        reasons.push(PasswordIssue::ContainsUsername);
+    }
+    let email = email.to_lowercase();
+    let local_part = email.split('@').next().unwrap_or("");
+    if (!email.is_empty() && lowered.contains(&email)) || (local_part.chars().count() >= 3 && lowered.contains(local_part)) {
+        reasons.push(PasswordIssue::ContainsEmail);
+    }
+    if is_compromised(password) {
+        reasons.push(PasswordIssue::Compromised);
+    }
+
+    if reasons.is_empty() {
+        Ok(())
+    } else {
+        Err(PasswordPolicyError {
+            error: String::from("Password does not meet the password policy"),
+            reasons,
+        })
+    }
+}
+
+/// Estimates password entropy in bits from the used character classes.
+///
+/// Runs of the same character only count once, so `aaaaaaaaaa` is not treated as a long password.
+fn estimate_entropy(password: &str) -> f64 {
+    let (mut lower, mut upper, mut digit, mut symbol, mut other) = (false, false, false, false, false);
+    let mut effective_length = 0;
+    let mut previous = None;
+    for c in password.chars() {
+        match c {
+            'a'..='z' => lower = true,
+            'A'..='Z' => upper = true,
+            '0'..='9' => digit = true,
+            c if c.is_ascii() => symbol = true,
+            _ => other = true,
+        }
+        if previous != Some(c) {
+            effective_length += 1;
+        }
+        previous = Some(c);
+    }
+    let pool = [(lower, 26), (upper, 26), (digit, 10), (symbol, 33), (other, 100)]
+        .iter()
+        .filter(|(used, _)| *used)
+        .map(|(_, size)| size)
+        .sum::<u32>();
+    if pool == 0 {
+        return 0.0;
+    }
+    effective_length as f64 * (pool as f64).log2()
+}
+
+/// Checks the password against the compromised password hash list.
+///
+/// The bundled list is always loaded, an additional list in the same format can be
+/// provided through the `COMPROMISED_PASSWORDS_PATH` environment variable.
+fn is_compromised(password: &str) -> bool {
+    let hashes = COMPROMISED.get_or_init(|| {
+        let mut hashes = HashSet::new();
+        load_hashes(&mut hashes, BUNDLED_COMPROMISED);
+        if let Ok(path) = std::env::var("COMPROMISED_PASSWORDS_PATH") {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => load_hashes(&mut hashes, &content),
+                Err(err) => tracing::warn!("Cannot load compromised passwords from {}: {}", path, err),
+            }
+        }
+        hashes
+    });
+    let digest = Sha1::digest(password.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02X}", byte)).collect();
+    hashes.contains(&hex)
+}
+
+// Lines may be in `HASH` or `HASH:COUNT` format
+fn load_hashes(hashes: &mut HashSet<String>, content: &str) {
+    for line in content.lines() {
+        let hash = line.split(':').next().unwrap_or("").trim();
+        if hash.len() == 40 {
+            hashes.insert(hash.to_uppercase());
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use scylla::Session;
+use uuid::Uuid;
+use crate::functions::lwt;
+use crate::notifier::{Notification, Notifier};
+use crate::security::passwords::verify_password;
+use crate::types::user::User;
+
+/// Fields that can only be changed after proving knowledge of the current password.
+pub const SENSITIVE_FIELDS: [&str; 2] = ["email", "password"];
+
+// How long (in seconds) a re-auth token stays valid
+const REAUTH_TOKEN_TTL: i32 = 300;
+
+/// Issues a short-lived re-auth token for the user.
+///
+/// The token is stored in `joltamp.reauth_tokens` with a TTL, so it expires on its own.
+pub async fn create_reauth_token(session: &Arc<Session>, user_id: Uuid) -> Result<Uuid> {
+    let token = Uuid::new_v4();
+    session.query_unpaged(format!("INSERT INTO joltamp.reauth_tokens (token, user_id) VALUES (?, ?) USING TTL {}", REAUTH_TOKEN_TTL),
+                          (token, user_id)).await?;
+    Ok(token)
+}
+
+/// Verifies that the request proves knowledge of the current password.
+///
+/// # Parameters
+/// - `user`: Filled user object of the authenticated user.
+/// - `current_password`: Plain text current password, if sent by the client.
+/// - `reauth_token`: Re-auth token obtained from `/api/v0/users/reauth`, if sent by the client.
+///
+/// # Returns
+/// `Ok(())` if either the password or the token is valid. Re-auth tokens are single use.
+pub async fn verify_reauth(session: &Arc<Session>, user: &User, current_password: Option<&str>, reauth_token: Option<Uuid>) -> Result<()> {
+    if let (Some(password), Some(hashed)) = (current_password, user.password.as_deref()) {
+        return verify_password(password, hashed).map_err(|_| Error::msg("Invalid current password"));
+    }
+    if let Some(token) = reauth_token {
+        let res = session.query_unpaged("SELECT user_id FROM joltamp.reauth_tokens WHERE token = ?", (token, ))
+            .await?.into_rows_result()?;
+        let (owner, ) = res.first_row::<(Uuid, )>().map_err(|_| Error::msg("Invalid re-auth token"))?;
+        if Some(owner) != user.user_id {
+            return Err(Error::msg("Invalid re-auth token"));
+        }
+        // Only one request can consume the token, a concurrent one finds it already deleted
+        let res = session.query_unpaged("DELETE FROM joltamp.reauth_tokens WHERE token = ? IF EXISTS", (token, )).await?;
+        if !lwt::applied(res)? {
+            return Err(Error::msg("Invalid re-auth token"));
+        }
+        return Ok(());
+    }
+    Err(Error::msg("Re-authentication required"))
+}
+
+/// Records a security relevant account change in `joltamp.security_events`
+/// and notifies the account owner on the e-mail the account had before the change.
+pub async fn record_security_event(session: &Arc<Session>, notifier: &Arc<dyn Notifier>, user_id: Uuid, email: &str, kind: &str) -> Result<()> {
+    session.query_unpaged("INSERT INTO joltamp.security_events (user_id, createdat, kind) VALUES (?, now(), ?)",
+                          (user_id, kind)).await?;
+    notifier.notify(email, Notification::SecurityEvent { kind: kind.to_string() }).await
+}
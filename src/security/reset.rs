@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use scylla::Session;
+use uuid::Uuid;
+use crate::functions::lwt;
+use crate::security::passwords::hash_password;
+use crate::types::user::{User, UserFunc};
+
+// How long (in seconds) a password reset token stays valid
+const RESET_TOKEN_TTL: i32 = 86400;
+
+/// Forces a password reset of the user.
+///
+/// The password is replaced with a random one, every session is revoked and a single use
+/// reset token is issued, which has to be delivered to the user to set a new password.
+pub async fn force_password_reset(session: &Arc<Session>, user: User) -> Result<Uuid> {
+    let mut scrambled = Uuid::new_v4().to_string();
+    let scrambled = hash_password(&mut scrambled).map_err(|_| Error::msg("Cannot scramble password"))?;
+    let res = session.query_unpaged("UPDATE joltamp.users SET password = ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                                    (scrambled, &user.username, &user.user_id, &user.createdat)).await?;
+    lwt::ensure_applied(res)?;
+    let user = user.revoke_sessions(session).await?;
+
+    let token = Uuid::new_v4();
+    session.query_unpaged(format!("INSERT INTO joltamp.password_resets (token, user_id) VALUES (?, ?) USING TTL {}", RESET_TOKEN_TTL),
+                          (token, user.user_id)).await?;
+    Ok(token)
+}
+
+/// Returns the id of the user a password reset token was issued for, without consuming it
+pub async fn reset_token_owner(session: &Arc<Session>, token: Uuid) -> Result<Uuid> {
+    let res = session.query_unpaged("SELECT user_id FROM joltamp.password_resets WHERE token = ?", (token, ))
+        .await?.into_rows_result()?;
+    let (user_id, ) = res.first_row::<(Uuid, )>().map_err(|_| Error::msg("Invalid reset token"))?;
+    Ok(user_id)
+}
+
+/// Consumes a password reset token.
+///
+/// # Returns
+/// `false` if the token was already consumed, e.g. by a concurrent request.
+pub async fn consume_reset_token(session: &Arc<Session>, token: Uuid) -> Result<bool> {
+    let res = session.query_unpaged("DELETE FROM joltamp.password_resets WHERE token = ? IF EXISTS", (token, )).await?;
+    lwt::applied(res)
+}
+
+/// Issues a consumed token again, when the password could not be stored after consuming it
+pub async fn restore_reset_token(session: &Arc<Session>, token: Uuid, user_id: Uuid) -> Result<()> {
+    session.query_unpaged(format!("INSERT INTO joltamp.password_resets (token, user_id) VALUES (?, ?) USING TTL {}", RESET_TOKEN_TTL),
+                          (token, user_id)).await?;
+    Ok(())
+}
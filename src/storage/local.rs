@@ -0,0 +1,64 @@
+use std::path::{Component, Path, PathBuf};
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::storage::Storage;
+
+/// Storage backend keeping files in a directory on the local filesystem.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> LocalStorage {
+        LocalStorage { root: root.into() }
+    }
+
+    // Refuses keys that could escape the storage root
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let key = Path::new(key);
+        if key.components().any(|component| !matches!(component, Component::Normal(_))) {
+            return Err(Error::msg("Invalid storage key"));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write to a temporary file first so readers never see a partial file,
+        // its name is unique so concurrent writes to the same key cannot mix
+        let mut temp = path.clone().into_os_string();
+        temp.push(format!(".{}.tmp", Uuid::new_v4().simple()));
+        let temp = PathBuf::from(temp);
+        let res = match tokio::fs::write(&temp, bytes).await {
+            Ok(()) => tokio::fs::rename(&temp, &path).await,
+            Err(err) => Err(err),
+        };
+        if res.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        Ok(res?)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        Ok(tokio::fs::read(self.path(key)?).await?)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.path(key)?).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+}
@@ -0,0 +1,16 @@
+pub mod local;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Blob storage for user uploaded files.
+///
+/// Keys are `/` separated relative paths built by the server (e.g. `avatars/<sha256>/128.png`),
+/// so the same content is always stored under the same key.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+    async fn exists(&self, key: &str) -> Result<bool>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
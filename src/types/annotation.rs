@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use anyhow::Result;
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Private nickname and note a user keeps about another user, only ever shown to its owner.
+///
+/// Stored in `joltamp.user_annotations` partitioned by `owner_id` and clustered by `target_id`.
+#[derive(Serialize, DeserializeRow)]
+pub struct Annotation {
+    pub target_id: Uuid,
+    pub nickname: Option<String>,
+    pub note: Option<String>,
+}
+
+impl Annotation {
+
+    /// Returns every annotation of the owner keyed by the annotated user
+    pub async fn for_owner(session: &Arc<Session>, owner_id: Uuid) -> Result<HashMap<Uuid, Annotation>> {
+        let res = session.query_unpaged("SELECT target_id, nickname, note FROM joltamp.user_annotations WHERE owner_id = ?",
+                                        (owner_id, )).await?.into_rows_result()?;
+        let mut annotations = HashMap::new();
+        for row in res.rows::<Annotation>()? {
+            let annotation = row?;
+            annotations.insert(annotation.target_id, annotation);
+        }
+        Ok(annotations)
+    }
+
+    /// Returns the annotation of the owner about the target, if any
+    pub async fn get(session: &Arc<Session>, owner_id: Uuid, target_id: Uuid) -> Result<Option<Annotation>> {
+        let res = session.query_unpaged("SELECT target_id, nickname, note FROM joltamp.user_annotations WHERE owner_id = ? AND target_id = ?",
+                                        (owner_id, target_id)).await?.into_rows_result()?;
+        Ok(res.maybe_first_row::<Annotation>()?)
+    }
+
+    /// Sets or clears (`None`) the nickname of the target
+    pub async fn set_nickname(session: &Arc<Session>, owner_id: Uuid, target_id: Uuid, nickname: Option<String>) -> Result<()> {
+        session.query_unpaged("UPDATE joltamp.user_annotations SET nickname = ? WHERE owner_id = ? AND target_id = ?",
+                              (nickname, owner_id, target_id)).await?;
+        Ok(())
+    }
+
+    /// Sets or clears (`None`) the note about the target
+    pub async fn set_note(session: &Arc<Session>, owner_id: Uuid, target_id: Uuid, note: Option<String>) -> Result<()> {
+        session.query_unpaged("UPDATE joltamp.user_annotations SET note = ? WHERE owner_id = ? AND target_id = ?",
+                              (note, owner_id, target_id)).await?;
+        Ok(())
+    }
+
+    /// Removes every annotation kept by the owner
+    pub async fn remove_all(session: &Arc<Session>, owner_id: Uuid) -> Result<()> {
+        session.query_unpaged("DELETE FROM joltamp.user_annotations WHERE owner_id = ?", (owner_id, )).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use chrono::{DateTime, Utc};
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::storage::Storage;
+
+/// How long an upload slot can be used before it expires.
+pub const UPLOAD_SLOT_SECONDS: i32 = 60 * 60;
+
+const ATTACHMENT_COLUMNS: &str = "attachment_id, owner_id, filename, size, content_type, width, height, status, createdat";
+
+/// Status of an attachment.
+pub const ATTACHMENT_PENDING: &str = "pending";
+pub const ATTACHMENT_UPLOADED: &str = "uploaded";
+
+/// File uploaded by a user, stored in `joltamp.attachments` keyed by `attachment_id`.
+///
+/// Uploads happen in two steps: a slot is requested with the filename and size, then the bytes
+/// are sent to it. Slots expire (row TTL) if nothing is uploaded in time. The bytes are kept
+/// in storage under `attachments/<attachment_id>/<filename>`.
+#[derive(Serialize, DeserializeRow)]
+pub struct Attachment {
+    pub attachment_id: Uuid,
+    pub owner_id: Uuid,
+    pub filename: String,
+    pub size: i64,
+    /// Sniffed from the uploaded bytes, `None` until the upload is done
+    pub content_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub status: String,
+    pub createdat: DateTime<Utc>,
+}
+
+impl Attachment {
+
+    /// Reserves an upload slot for a file of the given size
+    pub async fn create(session: &Arc<Session>, owner_id: Uuid, filename: String, size: i64) -> Result<Attachment> {
+        let attachment = Attachment {
+            attachment_id: Uuid::new_v4(),
+            owner_id,
+            filename,
+            size,
+            content_type: None,
+            width: None,
+            height: None,
+            status: String::from(ATTACHMENT_PENDING),
+            createdat: Utc::now(),
+        };
+        session.query_unpaged("INSERT INTO joltamp.attachments (attachment_id, owner_id, filename, size, status, createdat) VALUES (?, ?, ?, ?, ?, ?) USING TTL ?",
+                              (attachment.attachment_id, attachment.owner_id, &attachment.filename, attachment.size,
+                               &attachment.status, attachment.createdat, UPLOAD_SLOT_SECONDS)).await?;
+        Ok(attachment)
+    }
+
+    pub async fn from_attachment_id(session: &Arc<Session>, attachment_id: Uuid) -> Result<Attachment> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.attachments WHERE attachment_id = ?", ATTACHMENT_COLUMNS),
+                                        (attachment_id, )).await?.into_rows_result()?;
+        res.maybe_first_row::<Attachment>()?.ok_or_else(|| Error::msg("Attachment not found"))
+    }
+
+    /// Marks the upload as done with what was read from the bytes.
+    ///
+    /// Every column is written again without TTL, so the row no longer expires with the slot.
+    pub async fn complete_upload(&mut self, session: &Arc<Session>, content_type: &str, dimensions: Option<(u32, u32)>) -> Result<()> {
+        self.content_type = Some(content_type.to_string());
+        self.width = dimensions.map(|(width, _)| width as i32);
+        self.height = dimensions.map(|(_, height)| height as i32);
+        self.status = String::from(ATTACHMENT_UPLOADED);
+        session.query_unpaged(format!("INSERT INTO joltamp.attachments ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)", ATTACHMENT_COLUMNS),
+                              (self.attachment_id, self.owner_id, &self.filename, self.size, &self.content_type,
+                               self.width, self.height, &self.status, self.createdat)).await?;
+        Ok(())
+    }
+
+    /// Removes every attachment of the owner together with the stored files
+    pub async fn remove_all_for_owner(session: &Arc<Session>, storage: &Arc<dyn Storage>, owner_id: Uuid) -> Result<()> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.attachments WHERE owner_id = ? ALLOW FILTERING", ATTACHMENT_COLUMNS),
+                                        (owner_id, )).await?.into_rows_result()?;
+        for row in res.rows::<Attachment>()? {
+            let attachment = row?;
+            storage.delete(&attachment.key()).await?;
+            session.query_unpaged("DELETE FROM joltamp.attachments WHERE attachment_id = ?", (attachment.attachment_id, )).await?;
+        }
+        Ok(())
+    }
+
+    pub fn key(&self) -> String {
+        format!("attachments/{}/{}", self.attachment_id, self.filename)
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Entry of the admin audit log, stored in `joltamp.audit_log` partitioned by day.
+#[derive(Serialize, DeserializeRow)]
+pub struct AuditEntry {
+    pub day: NaiveDate,
+    pub createdat: DateTime<Utc>,
+    pub entry_id: Uuid,
+    pub admin_id: Uuid,
+    pub action: String,
+    pub target_id: Option<Uuid>,
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+
+    /// Records an action taken by an admin
+    pub async fn record(session: &Arc<Session>, admin_id: Uuid, action: &str, target_id: Option<Uuid>, detail: Option<String>) -> Result<()> {
+        let now = Utc::now();
+        session.query_unpaged("INSERT INTO joltamp.audit_log (day, createdat, entry_id, admin_id, action, target_id, detail) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                              (now.date_naive(), now, Uuid::new_v4(), admin_id, action, target_id, detail)).await?;
+        Ok(())
+    }
+
+    /// Records an action that already took effect, a failure is logged since the action cannot be undone
+    pub async fn record_logged(session: &Arc<Session>, admin_id: Uuid, action: &str, target_id: Option<Uuid>, detail: Option<String>) {
+        if let Err(err) = AuditEntry::record(session, admin_id, action, target_id, detail).await {
+            tracing::error!("Cannot record audit entry {} by {}: {:?}", action, admin_id, err);
+        }
+    }
+
+    /// Lists every entry recorded on the given day, newest first
+    pub async fn list_day(session: &Arc<Session>, day: NaiveDate) -> Result<Vec<AuditEntry>> {
+        let res = session.query_unpaged("SELECT day, createdat, entry_id, admin_id, action, target_id, detail FROM joltamp.audit_log WHERE day = ?",
+                                        (day, )).await?.into_rows_result()?;
+        let mut entries = Vec::new();
+        for row in res.rows::<AuditEntry>()? {
+            entries.push(row?);
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.createdat));
+        Ok(entries)
+    }
+}
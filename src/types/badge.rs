@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::types::types::ProfileQuery;
+
+const BADGE_COLUMNS: &str = "badge_id, name, description, icon, priority, retired";
+
+/// Badge of the badge catalog, stored in `joltamp.badges`.
+///
+/// Retired badges cannot be awarded anymore but still resolve for users who hold them.
+#[derive(Serialize, DeserializeRow, Clone)]
+pub struct Badge {
+    pub badge_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    /// Badges with higher priority are displayed first
+    pub priority: i32,
+    pub retired: Option<bool>,
+}
+
+impl Badge {
+
+    /// Creates a new badge in the catalog
+    pub async fn create(session: &Arc<Session>, name: String, description: Option<String>, icon: Option<String>, priority: i32) -> Result<Badge> {
+        let badge = Badge { badge_id: Uuid::new_v4(), name, description, icon, priority, retired: Some(false) };
+        badge.save(session).await?;
+        Ok(badge)
+    }
+
+    /// Writes every field of the badge
+    pub async fn save(&self, session: &Arc<Session>) -> Result<()> {
+        session.query_unpaged(format!("INSERT INTO joltamp.badges ({}) VALUES (?, ?, ?, ?, ?, ?)", BADGE_COLUMNS),
+                              (self.badge_id, &self.name, &self.description, &self.icon, self.priority, self.retired)).await?;
+        Ok(())
+    }
+
+    pub async fn from_badge_id(session: &Arc<Session>, badge_id: Uuid) -> Result<Badge> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.badges WHERE badge_id = ?", BADGE_COLUMNS),
+                                        (badge_id, )).await?.into_rows_result()?;
+        res.maybe_first_row::<Badge>()?.ok_or(Error::msg("Badge not found"))
+    }
+
+    /// Lists the whole catalog sorted by display priority, optionally including retired badges
+    pub async fn list_all(session: &Arc<Session>, include_retired: bool) -> Result<Vec<Badge>> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.badges", BADGE_COLUMNS), ())
+            .await?.into_rows_result()?;
+        let mut badges = Vec::new();
+        for badge in res.rows::<Badge>()? {
+            let badge = badge?;
+            if include_retired || !badge.retired.unwrap_or(false) {
+                badges.push(badge);
+            }
+        }
+        sort_by_priority(&mut badges);
+        Ok(badges)
+    }
+
+    /// Resolves a set of badge ids, unknown ids are skipped
+    pub async fn from_badge_ids(session: &Arc<Session>, badge_ids: &[Uuid]) -> Result<Vec<Badge>> {
+        if badge_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.badges WHERE badge_id IN ?", BADGE_COLUMNS),
+                                        (badge_ids, )).await?.into_rows_result()?;
+        let mut badges = Vec::new();
+        for badge in res.rows::<Badge>()? {
+            badges.push(badge?);
+        }
+        sort_by_priority(&mut badges);
+        Ok(badges)
+    }
+}
+
+/// Resolves the badges of a profile response when the client asked for `?expand=badges`
+pub async fn expand_badges(session: &Arc<Session>, query: &ProfileQuery, badge_ids: &Option<Vec<Uuid>>) -> Option<Vec<Badge>> {
+    if !query.expands("badges") {
+        return None;
+    }
+    let badge_ids = badge_ids.as_deref().unwrap_or_default();
+    Badge::from_badge_ids(session, badge_ids).await.ok()
+}
+
+fn sort_by_priority(badges: &mut [Badge]) {
+    badges.sort_by_key(|badge| std::cmp::Reverse(badge.priority));
+}
@@ -0,0 +1,58 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Serialize, Serializer};
+
+/// Hex RGB(A) color of a profile.
+///
+/// Parsed from `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` and always stored and
+/// serialized in the long lowercase form (`#rrggbb` or `#rrggbbaa`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: Option<u8>,
+}
+
+impl FromStr for HexColor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = value.trim().strip_prefix('#').ok_or("Color must start with #")?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(String::from("Color must be hexadecimal"));
+        }
+        // Short forms repeat every digit (#abc -> #aabbcc)
+        let channels: Vec<u8> = match hex.len() {
+            3 | 4 => hex.chars()
+                .map(|c| u8::from_str_radix(&format!("{}{}", c, c), 16).unwrap_or(0))
+                .collect(),
+            6 | 8 => (0..hex.len()).step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0))
+                .collect(),
+            _ => return Err(String::from("Color must be #RGB, #RGBA, #RRGGBB or #RRGGBBAA")),
+        };
+        Ok(HexColor {
+            r: channels[0],
+            g: channels[1],
+            b: channels[2],
+            a: channels.get(3).copied(),
+        })
+    }
+}
+
+impl Display for HexColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if let Some(a) = self.a {
+            write!(f, "{:02x}", a)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for HexColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use futures::TryStreamExt;
+use scylla::batch::{Batch, BatchType};
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::functions::images::ImageKind;
+use crate::functions::lwt;
+use crate::storage::Storage;
+use crate::types::annotation::Annotation;
+use crate::types::attachment::Attachment;
+use crate::types::search::SearchIndex;
+use crate::types::settings::SettingsDocument;
+use crate::types::token::ApiToken;
+use crate::types::user::{User, UserFunc};
+use crate::types::username::UsernameClaim;
+
+/// Time between the deletion request and the permanent removal of the account.
+pub const DELETION_GRACE_DAYS: i64 = 14;
+
+/// Display name shown instead of the profile of a deleted account.
+pub const DELETED_DISPLAYNAME: &str = "Deleted User";
+
+/// Pending deletion of an account, stored in `joltamp.account_deletions` (one row per user).
+///
+/// The account is deactivated while the row exists. Logging in before `purgeat` cancels the
+/// deletion, afterwards the account is replaced by an anonymous tombstone.
+#[derive(Serialize, DeserializeRow)]
+pub struct AccountDeletion {
+    pub user_id: Uuid,
+    pub requestedat: DateTime<Utc>,
+    pub purgeat: DateTime<Utc>,
+}
+
+// Columns of joltamp.users needed to address a row and edit its friends
+#[derive(DeserializeRow)]
+struct FriendsRow {
+    username: String,
+    user_id: Uuid,
+    createdat: NaiveDate,
+    friends: HashMap<Uuid, i8>,
+}
+
+impl AccountDeletion {
+
+    /// Deactivates the account and schedules its removal after the grace period
+    pub async fn request(session: &Arc<Session>, user: User) -> Result<AccountDeletion> {
+        let now = Utc::now();
+        let deletion = AccountDeletion {
+            user_id: user.user_id.unwrap_or(Uuid::nil()),
+            requestedat: now,
+            purgeat: now + Duration::days(DELETION_GRACE_DAYS),
+        };
+        session.query_unpaged("INSERT INTO joltamp.account_deletions (user_id, requestedat, purgeat) VALUES (?, ?, ?)",
+                              (deletion.user_id, deletion.requestedat, deletion.purgeat)).await?;
+        let res = session.query_unpaged("UPDATE joltamp.users SET deactivated = true WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                                        (&user.username, &user.user_id, &user.createdat)).await?;
+        lwt::ensure_applied(res)?;
+        user.revoke_sessions(session).await?;
+        Ok(deletion)
+    }
+
+    /// Returns the pending deletion of the user, if any
+    pub async fn pending_for(session: &Arc<Session>, user_id: Uuid) -> Result<Option<AccountDeletion>> {
+        let res = session.query_unpaged("SELECT user_id, requestedat, purgeat FROM joltamp.account_deletions WHERE user_id = ?",
+                                        (user_id, )).await?.into_rows_result()?;
+        Ok(res.maybe_first_row::<AccountDeletion>()?)
+    }
+
+    /// Cancels the deletion and reactivates the account
+    pub async fn cancel(session: &Arc<Session>, user: &User) -> Result<()> {
+        let res = session.query_unpaged("UPDATE joltamp.users SET deactivated = false WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                                        (&user.username, &user.user_id, &user.createdat)).await?;
+        lwt::ensure_applied(res)?;
+        session.query_unpaged("DELETE FROM joltamp.account_deletions WHERE user_id = ?", (user.user_id, )).await?;
+        Ok(())
+    }
+
+    /// Purges every account whose grace period is over.
+    ///
+    /// An account that cannot be purged is logged and retried on the next run, it does not hold back the others.
+    pub async fn purge_due(session: &Arc<Session>, storage: &Arc<dyn Storage>) -> Result<()> {
+        let mut rows = session.query_iter("SELECT user_id, requestedat, purgeat FROM joltamp.account_deletions", ())
+            .await?.rows_stream::<AccountDeletion>()?;
+        let mut due = Vec::new();
+        while let Some(deletion) = rows.try_next().await? {
+            if deletion.purgeat <= Utc::now() {
+                due.push(deletion);
+            }
+        }
+        AccountDeletion::purge_all(session, storage, due).await
+    }
+
+    /// Permanently removes the account, see `purge_all`
+    pub async fn purge(self, session: &Arc<Session>, storage: &Arc<dyn Storage>) -> Result<()> {
+        AccountDeletion::purge_all(session, storage, vec![self]).await
+    }
+
+    /// Permanently removes the accounts and the bots they own.
+    ///
+    /// Credentials, tokens, profile data and images, username history, private notes, settings and
+    /// attachments are dropped, the users are removed from the `friends` map of every other user and
+    /// each row is replaced by a tombstone that keeps only the user id, so references to it still
+    /// resolve to "Deleted User".
+    /// The `friends` maps are cleaned in a single scan for all accounts.
+    async fn purge_all(session: &Arc<Session>, storage: &Arc<dyn Storage>, deletions: Vec<AccountDeletion>) -> Result<()> {
+        let mut purged = HashSet::new();
+        for deletion in &deletions {
+            match purge_account(session, storage, deletion.user_id).await {
+                Ok(bots) => {
+                    purged.insert(deletion.user_id);
+                    purged.extend(bots);
+                }
+                Err(err) => tracing::error!("Cannot purge account {}: {:?}", deletion.user_id, err),
+            }
+        }
+        if purged.is_empty() {
+            return Ok(());
+        }
+        remove_from_friends(session, &purged).await?;
+
+        // Only dropped once everything is done, so an interrupted purge is retried
+        for deletion in deletions.iter().filter(|deletion| purged.contains(&deletion.user_id)) {
+            session.query_unpaged("DELETE FROM joltamp.account_deletions WHERE user_id = ?", (deletion.user_id, )).await?;
+        }
+        Ok(())
+    }
+}
+
+// Erases the account and every bot it owns, returns the ids of the bots
+async fn purge_account(session: &Arc<Session>, storage: &Arc<dyn Storage>, user_id: Uuid) -> Result<Vec<Uuid>> {
+    let bots = User::owned_bots(session, user_id).await?;
+    for bot_id in &bots {
+        erase_user(session, storage, *bot_id).await?;
+    }
+    erase_user(session, storage, user_id).await?;
+    Ok(bots)
+}
+
+// Replaces the row with a tombstone and drops everything stored about the user, except for `friends` maps of others
+async fn erase_user(session: &Arc<Session>, storage: &Arc<dyn Storage>, user_id: Uuid) -> Result<()> {
+    if let Ok(user) = User::from_user_id(user_id).fill_info(session).await {
+        let mut batch = Batch::new(BatchType::Logged);
+        batch.append_statement("DELETE FROM joltamp.users WHERE username = ? AND user_id = ? AND createdat = ?");
+        batch.append_statement("INSERT INTO joltamp.users (createdat, user_id, username, displayname, email, password, isadmin, jwt, status, isbot, deleted) VALUES (?, ?, ?, ?, '', '', false, ?, 0, ?, true)");
+        session.batch(&batch, (
+            (&user.username, &user.user_id, &user.createdat),
+            (&user.createdat, &user.user_id, format!("deleted-{}", user_id.simple()), DELETED_DISPLAYNAME, Uuid::new_v4(), &user.isbot),
+        )).await?;
+        SearchIndex::remove(session, user_id, user.username.as_deref().unwrap_or(""), user.displayname.as_deref().unwrap_or("")).await?;
+        UsernameClaim::release(session, user.username.as_deref().unwrap_or(""), user_id).await?;
+        if let Some(hash) = &user.avatar {
+            remove_image(session, storage, ImageKind::Avatar, hash).await?;
+        }
+        if let Some(hash) = &user.banner {
+            remove_image(session, storage, ImageKind::Banner, hash).await?;
+        }
+    }
+    ApiToken::revoke_all(session, user_id).await?;
+    session.query_unpaged("DELETE FROM joltamp.username_history WHERE user_id = ?", (user_id, )).await?;
+    Annotation::remove_all(session, user_id).await?;
+    SettingsDocument::remove(session, user_id).await?;
+    Attachment::remove_all_for_owner(session, storage, user_id).await?;
+    Ok(())
+}
+
+/// Removes the stored variants of an avatar or banner that is no longer used.
+///
+/// Images are content addressed, the files are only removed if no other user has the same image.
+pub async fn remove_image(session: &Arc<Session>, storage: &Arc<dyn Storage>, kind: ImageKind, hash: &str) -> Result<()> {
+    let res = session.query_unpaged(format!("SELECT user_id FROM joltamp.users WHERE {} = ? LIMIT 1 ALLOW FILTERING", kind.column()), (hash, ))
+        .await?.into_rows_result()?;
+    if res.rows_num() > 0 {
+        return Ok(());
+    }
+    for size in kind.sizes() {
+        storage.delete(&kind.key(hash, *size)).await?;
+    }
+    Ok(())
+}
+
+// Friendships are stored on both sides, blocks and pending requests only on one, so every map is checked
+async fn remove_from_friends(session: &Arc<Session>, user_ids: &HashSet<Uuid>) -> Result<()> {
+    let mut rows = session.query_iter("SELECT username, user_id, createdat, friends FROM joltamp.users", ())
+        .await?.rows_stream::<FriendsRow>()?;
+    while let Some(row) = rows.try_next().await? {
+        let removed: Vec<Uuid> = row.friends.keys().filter(|id| user_ids.contains(id)).copied().collect();
+        if !removed.is_empty() {
+            // A row renamed since the scan is skipped instead of recreated under its old username
+            session.query_unpaged("UPDATE joltamp.users SET friends = friends - ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                                  (removed, &row.username, &row.user_id, &row.createdat)).await?;
+        }
+    }
+    Ok(())
+}
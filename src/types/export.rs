@@ -0,0 +1,264 @@
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+use crate::functions::lwt;
+use crate::storage::Storage;
+use crate::types::annotation::Annotation;
+use crate::types::settings::SettingsDocument;
+use crate::types::token::ApiToken;
+use crate::types::user::{User, UserFunc};
+use crate::types::username::UsernameChange;
+
+/// How long a finished archive can be downloaded before it is removed.
+pub const EXPORT_EXPIRY_DAYS: i64 = 7;
+
+const EXPORT_COLUMNS: &str = "job_id, user_id, status, createdat, expiresat, download_token";
+
+/// Status of an export job.
+pub const EXPORT_PENDING: &str = "pending";
+pub const EXPORT_READY: &str = "ready";
+pub const EXPORT_FAILED: &str = "failed";
+pub const EXPORT_DOWNLOADED: &str = "downloaded";
+
+/// Personal data export of a user, stored in `joltamp.export_jobs` keyed by `job_id`.
+///
+/// The archive is kept in storage under `exports/<job_id>.zip` until it is downloaded
+/// once or `expiresat` passes, whichever comes first.
+#[derive(Serialize, DeserializeRow)]
+pub struct ExportJob {
+    pub job_id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub createdat: DateTime<Utc>,
+    pub expiresat: DateTime<Utc>,
+    #[serde(skip)]
+    pub download_token: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+struct SecurityEvent {
+    createdat: DateTime<Utc>,
+    kind: String,
+}
+
+impl ExportJob {
+
+    /// Starts an export of the user, unless one is already pending or ready to download.
+    ///
+    /// The open job of a user is tracked in `joltamp.active_exports` (keyed by `user_id`) and claimed
+    /// with a lightweight transaction, so concurrent requests cannot start two exports.
+    ///
+    /// # Returns
+    /// The job and whether it was started by this call.
+    pub async fn start(session: &Arc<Session>, storage: &Arc<dyn Storage>, user_id: Uuid) -> Result<(ExportJob, bool)> {
+        let now = Utc::now();
+        let job = ExportJob {
+            job_id: Uuid::new_v4(),
+            user_id,
+            status: String::from(EXPORT_PENDING),
+            createdat: now,
+            expiresat: now + Duration::days(EXPORT_EXPIRY_DAYS),
+            download_token: None,
+        };
+        // A claim left behind by a job that is no longer open is removed once, then claimed again
+        for _ in 0..2 {
+            let res = session.query_unpaged(format!("INSERT INTO joltamp.active_exports (user_id, job_id) VALUES (?, ?) IF NOT EXISTS USING TTL {}", EXPORT_EXPIRY_DAYS * 24 * 60 * 60),
+                                            (user_id, job.job_id)).await?;
+            if lwt::applied(res)? {
+                ExportJob::spawn(session, storage, &job).await?;
+                return Ok((job, true));
+            }
+            let res = session.query_unpaged("SELECT job_id FROM joltamp.active_exports WHERE user_id = ?", (user_id, ))
+                .await?.into_rows_result()?;
+            let Some((open_id, )) = res.maybe_first_row::<(Uuid, )>()? else {
+                continue;
+            };
+            match ExportJob::from_job_id(session, open_id).await {
+                Ok(open) if (open.status == EXPORT_PENDING || open.status == EXPORT_READY) && open.expiresat > Utc::now() => return Ok((open, false)),
+                _ => ExportJob::release(session, user_id, open_id).await?,
+            }
+        }
+        Err(Error::msg("Cannot start export"))
+    }
+
+    // Writes the pending job and builds its archive in the background
+    async fn spawn(session: &Arc<Session>, storage: &Arc<dyn Storage>, job: &ExportJob) -> Result<()> {
+        let res = session.query_unpaged("INSERT INTO joltamp.export_jobs (job_id, user_id, status, createdat, expiresat) VALUES (?, ?, ?, ?, ?)",
+                                        (job.job_id, job.user_id, &job.status, job.createdat, job.expiresat)).await;
+        if let Err(err) = res {
+            ExportJob::release(session, job.user_id, job.job_id).await?;
+            return Err(err.into());
+        }
+
+        let (session, storage) = (session.clone(), storage.clone());
+        let (job_id, user_id, expiresat) = (job.job_id, job.user_id, job.expiresat);
+        tokio::spawn(async move {
+            // Built in its own task, so a panic marks the job as failed instead of leaving it pending
+            let build = tokio::spawn({
+                let (session, storage) = (session.clone(), storage.clone());
+                async move { storage.put(&ExportJob::key(job_id), build_archive(&session, user_id).await?).await }
+            });
+            let result = match build.await {
+                Ok(result) => result,
+                Err(err) => Err(Error::msg(format!("Export task panicked: {}", err))),
+            };
+            let res = match result {
+                Ok(()) => ExportJob::mark_ready(&session, job_id, expiresat).await,
+                Err(err) => {
+                    tracing::error!("Export {} failed: {:?}", job_id, err);
+                    ExportJob::mark_failed(&session, user_id, job_id).await
+                }
+            };
+            if let Err(err) = res {
+                tracing::error!("Export {} status update failed: {:?}", job_id, err);
+            }
+        });
+        Ok(())
+    }
+
+    // The lookup row by token is written first, so a ready job can always be downloaded
+    async fn mark_ready(session: &Arc<Session>, job_id: Uuid, expiresat: DateTime<Utc>) -> Result<()> {
+        let token = Uuid::new_v4();
+        let ttl = (expiresat - Utc::now()).num_seconds().max(1);
+        session.query_unpaged(format!("INSERT INTO joltamp.export_downloads (download_token, job_id) VALUES (?, ?) USING TTL {}", ttl),
+                              (token, job_id)).await?;
+        session.query_unpaged("UPDATE joltamp.export_jobs SET status = ?, download_token = ? WHERE job_id = ?",
+                              (EXPORT_READY, token, job_id)).await?;
+        Ok(())
+    }
+
+    async fn mark_failed(session: &Arc<Session>, user_id: Uuid, job_id: Uuid) -> Result<()> {
+        session.query_unpaged("UPDATE joltamp.export_jobs SET status = ? WHERE job_id = ?", (EXPORT_FAILED, job_id)).await?;
+        ExportJob::release(session, user_id, job_id).await
+    }
+
+    // Lets the user start a new export, unless a newer job holds the claim already
+    async fn release(session: &Arc<Session>, user_id: Uuid, job_id: Uuid) -> Result<()> {
+        session.query_unpaged("DELETE FROM joltamp.active_exports WHERE user_id = ? IF job_id = ?", (user_id, job_id)).await?;
+        Ok(())
+    }
+
+    pub async fn from_job_id(session: &Arc<Session>, job_id: Uuid) -> Result<ExportJob> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.export_jobs WHERE job_id = ?", EXPORT_COLUMNS),
+                                        (job_id, )).await?.into_rows_result()?;
+        res.first_row::<ExportJob>().map_err(|_| Error::msg("Export not found"))
+    }
+
+    /// Exchanges a download token for the archive.
+    ///
+    /// The job is looked up in `joltamp.export_downloads` keyed by `download_token`. The token is
+    /// single use: the archive is read, the job is marked as downloaded, then the archive is removed.
+    pub async fn consume(session: &Arc<Session>, storage: &Arc<dyn Storage>, token: Uuid) -> Result<Vec<u8>> {
+        let res = session.query_unpaged("SELECT job_id FROM joltamp.export_downloads WHERE download_token = ?", (token, ))
+            .await?.into_rows_result()?;
+        let (job_id, ) = res.first_row::<(Uuid, )>().map_err(|_| Error::msg("Invalid download link"))?;
+        let job = ExportJob::from_job_id(session, job_id).await?;
+        if job.status != EXPORT_READY || job.download_token != Some(token) || job.expiresat <= Utc::now() {
+            return Err(Error::msg("Invalid download link"));
+        }
+        // Read before the status flips, a failed read leaves the link usable
+        let archive = storage.get(&ExportJob::key(job.job_id)).await?;
+        // Only one of several concurrent downloads gets to flip the status
+        let res = session.query_unpaged("UPDATE joltamp.export_jobs SET status = ?, download_token = null WHERE job_id = ? IF status = ?",
+                                        (EXPORT_DOWNLOADED, job.job_id, EXPORT_READY)).await?;
+        if !lwt::applied(res)? {
+            return Err(Error::msg("Invalid download link"));
+        }
+        storage.delete(&ExportJob::key(job.job_id)).await?;
+        session.query_unpaged("DELETE FROM joltamp.export_downloads WHERE download_token = ?", (token, )).await?;
+        ExportJob::release(session, job.user_id, job.job_id).await?;
+        Ok(archive)
+    }
+
+    /// Removes expired jobs together with their archives
+    pub async fn remove_expired(session: &Arc<Session>, storage: &Arc<dyn Storage>) -> Result<()> {
+        let mut rows = session.query_iter(format!("SELECT {} FROM joltamp.export_jobs", EXPORT_COLUMNS), ())
+            .await?.rows_stream::<ExportJob>()?;
+        let mut expired = Vec::new();
+        while let Some(job) = rows.try_next().await? {
+            if job.expiresat <= Utc::now() {
+                expired.push(job.job_id);
+            }
+        }
+        for job_id in expired {
+            storage.delete(&ExportJob::key(job_id)).await?;
+            session.query_unpaged("DELETE FROM joltamp.export_jobs WHERE job_id = ?", (job_id, )).await?;
+        }
+        Ok(())
+    }
+
+    fn key(job_id: Uuid) -> String {
+        format!("exports/{}.zip", job_id)
+    }
+}
+
+// Gathers everything stored about the user into a ZIP of JSON files
+async fn build_archive(session: &Arc<Session>, user_id: Uuid) -> Result<Vec<u8>> {
+    let user = User::from_user_id(user_id).fill_info(session).await?.fetch_friends(session).await?;
+
+    let profile = json!({
+        "user_id": user.user_id,
+        "createdat": user.createdat,
+        "username": user.username,
+        "email": user.email,
+        "displayname": user.displayname,
+        "status": user.status,
+        "bannercolor": user.bannercolor,
+        "backgroundcolor": user.backgroundcolor,
+        "desc": user.desc,
+        "pronouns": user.pronouns,
+        "links": user.links,
+        "avatar": user.avatar,
+        "banner": user.banner,
+        "badges": user.badges,
+        "isadmin": user.isadmin,
+        "isbot": user.isbot,
+        "owner_id": user.owner_id,
+        "username_history": UsernameChange::list_for_user(session, user_id).await?,
+    });
+    let settings = json!({
+        "privacy": user.privacy.unwrap_or_default(),
+        "client": SettingsDocument::for_user(session, user_id).await?,
+    });
+    let friends: Vec<Value> = user.friends.unwrap_or_default().into_values().map(|friend| json!({
+        "user_id": friend.user_id,
+        "username": friend.username,
+        "friendstatus": friend.friendstatus,
+        "nickname": friend.nickname,
+    })).collect();
+    let notes: Vec<Annotation> = Annotation::for_owner(session, user_id).await?.into_values().collect();
+
+    let res = session.query_unpaged("SELECT toTimestamp(createdat), kind FROM joltamp.security_events WHERE user_id = ?",
+                                    (user_id, )).await?.into_rows_result()?;
+    let mut security_events = Vec::new();
+    for row in res.rows::<(DateTime<Utc>, String)>()? {
+        let (createdat, kind) = row?;
+        security_events.push(SecurityEvent { createdat, kind });
+    }
+    let sessions = json!({
+        "api_tokens": ApiToken::list_for_user(session, user_id).await?,
+        "security_events": security_events,
+    });
+
+    let files = [
+        ("profile.json", profile),
+        ("settings.json", settings),
+        ("friends.json", Value::from(friends)),
+        ("notes.json", serde_json::to_value(notes)?),
+        ("sessions.json", sessions),
+    ];
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(&serde_json::to_vec_pretty(&content)?)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
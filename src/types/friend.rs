@@ -3,10 +3,25 @@ use std::sync::Arc;
 use scylla::Session;
 use uuid::Uuid;
 use anyhow::Result;
-use chrono::NaiveDate;
 use serde::Serialize;
+use crate::types::color::HexColor;
+use crate::types::privacy::{PrivacySettings, Relation};
 use crate::types::user::User;
 
+/// Values of the `friends` map of a user, keyed by the other user's id.
+/// `0` and `1` are pending requests.
+pub const FRIEND_STATUS_FRIENDS: i8 = 2;
+/// The user blocked the other user.
+pub const FRIEND_STATUS_BLOCKED: i8 = 3;
+
+/// Returns the users who are friends of both users, given their raw `friends` maps
+pub fn mutual_friends(first: &HashMap<Uuid, i8>, second: &HashMap<Uuid, i8>) -> Vec<Uuid> {
+    first.iter()
+        .filter(|(id, status)| **status == FRIEND_STATUS_FRIENDS && second.get(*id) == Some(&FRIEND_STATUS_FRIENDS))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
 #[derive(Serialize)]
 pub struct Friend{
     pub friendstatus: u8,
@@ -14,9 +29,14 @@ pub struct Friend{
     pub username: Option<String>,
     pub badges: Option<Vec<Uuid>>,
     pub displayname: Option<String>,
-    pub bannercolor: Option<String>,
-    pub backgroundcolor: Option<String>,
+    pub bannercolor: Option<HexColor>,
+    pub backgroundcolor: Option<HexColor>,
     pub status: Option<i8>,
+    pub avatar: Option<String>,
+    pub banner: Option<String>,
+    pub isbot: Option<bool>,
+    /// Private nickname the viewer set for the friend
+    pub nickname: Option<String>,
 }
 
 pub trait FriendFunc: std::marker::Sized {
@@ -25,16 +45,24 @@ pub trait FriendFunc: std::marker::Sized {
 
 impl FriendFunc for Friend {
     async fn fill_info(mut self, session: &Arc<Session>) -> Result<Self> {
-        let res = session.query_unpaged("SELECT username, badges, displayname, bannercolor, backgroundcolor, status FROM joltamp.users WHERE user_id = ? ALLOW FILTERING",
+        let res = session.query_unpaged("SELECT username, badges, displayname, bannercolor, backgroundcolor, status, avatar, banner, isbot, presence_visible FROM joltamp.users WHERE user_id = ? ALLOW FILTERING",
                                     (&self.user_id, )).await?.into_rows_result()?;
-        let (username, badges, displayname, bannercolor, backgroundcolor, status)
-            = res.first_row::<(String, Option<Vec<Uuid>>, String, Option<String>, Option<String>, i8)>()?;
+        let (username, badges, displayname, bannercolor, backgroundcolor, status, avatar, banner, isbot, presence_visible)
+            = res.first_row::<(String, Option<Vec<Uuid>>, String, Option<String>, Option<String>, i8, Option<String>, Option<String>, Option<bool>, Option<bool>)>()?;
         self.username = Some(username);
         self.badges = badges;
         self.displayname = Some(displayname);
-        self.bannercolor = bannercolor;
-        self.backgroundcolor = backgroundcolor;
-        self.status = Some(status);
+        self.bannercolor = bannercolor.and_then(|color| color.parse().ok());
+        self.backgroundcolor = backgroundcolor.and_then(|color| color.parse().ok());
+        // Pending requests and blocked users only see the status if it is public
+        let relation = if self.friendstatus as i8 == FRIEND_STATUS_FRIENDS { Relation::Friend } else { Relation::Stranger };
+        let privacy = PrivacySettings::from_columns(None, None, presence_visible, None);
+        if privacy.shows_presence(relation) {
+            self.status = Some(status);
+        }
+        self.avatar = avatar;
+        self.banner = banner;
+        self.isbot = isbot;
 
         Ok(self)
 
@@ -52,9 +80,20 @@ impl Friend {
             bannercolor: None,
             backgroundcolor: None,
             status: None,
+            avatar: None,
+            banner: None,
+            isbot: None,
+            nickname: None,
         }
     }
-    async fn from_user(self, user: User) -> Friend {
+    /// Builds the entry of the user as seen by a viewer, the status follows the presence setting
+    pub fn for_viewer(user: User, relation: Relation) -> Friend {
+        let privacy = user.privacy.unwrap_or_default();
+        let mut friend = Friend::from_user(user);
+        friend.status = friend.status.filter(|_| privacy.shows_presence(relation));
+        friend
+    }
+    fn from_user(user: User) -> Friend {
         Friend {
             friendstatus: 0,
             user_id: user.user_id.unwrap(),
@@ -64,6 +103,10 @@ impl Friend {
             bannercolor: user.bannercolor,
             backgroundcolor: user.backgroundcolor,
             status: user.status,
+            avatar: user.avatar,
+            banner: user.banner,
+            isbot: user.isbot,
+            nickname: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use anyhow::Result;
+use scylla::Session;
+use crate::types::search::SearchIndex;
+use crate::types::username::UsernameClaim;
+
+/// Indexes the users created before the search index existed.
+pub const SEARCH_INDEX_MIGRATION: &str = "search_index";
+/// Claims the usernames of the users created before `joltamp.usernames` existed.
+pub const USERNAMES_MIGRATION: &str = "usernames";
+
+/// One-off data migrations run at startup, stored in `joltamp.migrations` keyed by `name`.
+///
+/// A row is written once a migration completed, so it is skipped on the next start.
+/// A migration interrupted before that runs again, migrations must therefore be idempotent.
+pub struct Migration;
+
+impl Migration {
+
+    /// Runs every migration that did not complete yet, in order
+    pub async fn run_pending(session: &Arc<Session>) -> Result<()> {
+        if !Migration::is_done(session, SEARCH_INDEX_MIGRATION).await? {
+            SearchIndex::rebuild(session).await?;
+            Migration::mark_done(session, SEARCH_INDEX_MIGRATION).await?;
+        }
+        if !Migration::is_done(session, USERNAMES_MIGRATION).await? {
+            UsernameClaim::backfill(session).await?;
+            Migration::mark_done(session, USERNAMES_MIGRATION).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the migration already completed
+    pub async fn is_done(session: &Arc<Session>, name: &str) -> Result<bool> {
+        let res = session.query_unpaged("SELECT name FROM joltamp.migrations WHERE name = ?", (name, ))
+            .await?.into_rows_result()?;
+        Ok(res.rows_num() > 0)
+    }
+
+    /// Records that the migration completed
+    pub async fn mark_done(session: &Arc<Session>, name: &str) -> Result<()> {
+        session.query_unpaged("INSERT INTO joltamp.migrations (name, completedat) VALUES (?, toTimestamp(now()))", (name, )).await?;
+        Ok(())
+    }
+}
@@ -1,3 +1,19 @@
+#[allow(clippy::module_inception)]
 pub mod types;
 pub mod user;
-pub mod friend;
\ No newline at end of file
+pub mod friend;
+pub mod token;
+pub mod audit;
+pub mod suspension;
+pub mod badge;
+pub mod profile;
+pub mod color;
+pub mod username;
+pub mod deletion;
+pub mod export;
+pub mod privacy;
+pub mod settings;
+pub mod annotation;
+pub mod attachment;
+pub mod search;
+pub mod migration;
\ No newline at end of file
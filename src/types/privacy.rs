@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use anyhow::Result;
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::functions::lwt;
+use crate::types::friend::{mutual_friends, FRIEND_STATUS_BLOCKED, FRIEND_STATUS_FRIENDS};
+use crate::types::user::User;
+
+/// Who may send the user a friend request.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FriendRequests {
+    Everyone,
+    FriendsOfFriends,
+    Nobody,
+}
+
+/// Who may send the user a direct message.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirectMessages {
+    Everyone,
+    Friends,
+    Nobody,
+}
+
+impl FriendRequests {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FriendRequests::Everyone => "everyone",
+            FriendRequests::FriendsOfFriends => "friends-of-friends",
+            FriendRequests::Nobody => "nobody",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<FriendRequests> {
+        match value {
+            "everyone" => Some(FriendRequests::Everyone),
+            "friends-of-friends" => Some(FriendRequests::FriendsOfFriends),
+            "nobody" => Some(FriendRequests::Nobody),
+            _ => None,
+        }
+    }
+}
+
+impl DirectMessages {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DirectMessages::Everyone => "everyone",
+            DirectMessages::Friends => "friends",
+            DirectMessages::Nobody => "nobody",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<DirectMessages> {
+        match value {
+            "everyone" => Some(DirectMessages::Everyone),
+            "friends" => Some(DirectMessages::Friends),
+            "nobody" => Some(DirectMessages::Nobody),
+            _ => None,
+        }
+    }
+}
+
+/// Privacy settings of a user, stored in the `friend_requests`, `direct_messages`,
+/// `presence_visible` and `discoverable` columns of `joltamp.users`. Unset columns use the defaults.
+#[derive(Serialize, Clone, Copy)]
+pub struct PrivacySettings {
+    pub friend_requests: FriendRequests,
+    pub direct_messages: DirectMessages,
+    /// Whether users who are not friends can see the status
+    pub presence_visible: bool,
+    /// Whether the user can be found through user search by users who are not friends
+    pub discoverable: bool,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        PrivacySettings {
+            friend_requests: FriendRequests::Everyone,
+            direct_messages: DirectMessages::Friends,
+            presence_visible: true,
+            discoverable: true,
+        }
+    }
+}
+
+/// Relation of the user viewing a profile to its owner.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Relation {
+    Myself,
+    Friend,
+    FriendOfFriend,
+    Stranger,
+    /// The owner of the profile blocked the viewer
+    BlockedBy,
+}
+
+impl Relation {
+
+    /// Determines the relation of `viewer` to `target`, anonymous viewers are strangers.
+    ///
+    /// Both users need their `relations` filled, as done by `fill_info`.
+    pub fn between(viewer: Option<&User>, target: &User) -> Relation {
+        let Some(viewer) = viewer else {
+            return Relation::Stranger;
+        };
+        let (Some(viewer_id), Some(target_id)) = (viewer.user_id, target.user_id) else {
+            return Relation::Stranger;
+        };
+        let empty = HashMap::new();
+        Relation::from_maps(viewer_id, viewer.relations.as_ref().unwrap_or(&empty), target_id, target.relations.as_ref().unwrap_or(&empty))
+    }
+
+    /// Determines the relation from the raw `friends` maps of both users
+    pub fn from_maps(viewer_id: Uuid, viewer_relations: &HashMap<Uuid, i8>, target_id: Uuid, target_relations: &HashMap<Uuid, i8>) -> Relation {
+        if viewer_id == target_id {
+            return Relation::Myself;
+        }
+        if target_relations.get(&viewer_id) == Some(&FRIEND_STATUS_BLOCKED) {
+            return Relation::BlockedBy;
+        }
+        if viewer_relations.get(&target_id) == Some(&FRIEND_STATUS_FRIENDS) {
+            return Relation::Friend;
+        }
+        if !mutual_friends(viewer_relations, target_relations).is_empty() {
+            Relation::FriendOfFriend
+        } else {
+            Relation::Stranger
+        }
+    }
+}
+
+impl PrivacySettings {
+
+    /// Builds the settings from their stored columns, invalid or unset values fall back to defaults
+    pub fn from_columns(friend_requests: Option<String>, direct_messages: Option<String>, presence_visible: Option<bool>, discoverable: Option<bool>) -> PrivacySettings {
+        let defaults = PrivacySettings::default();
+        PrivacySettings {
+            friend_requests: friend_requests.as_deref().and_then(FriendRequests::parse).unwrap_or(defaults.friend_requests),
+            direct_messages: direct_messages.as_deref().and_then(DirectMessages::parse).unwrap_or(defaults.direct_messages),
+            presence_visible: presence_visible.unwrap_or(defaults.presence_visible),
+            discoverable: discoverable.unwrap_or(defaults.discoverable),
+        }
+    }
+
+    /// Writes every setting of the user
+    pub async fn save(&self, session: &Arc<Session>, user: &User) -> Result<()> {
+        let res = session.query_unpaged("UPDATE joltamp.users SET friend_requests = ?, direct_messages = ?, presence_visible = ?, discoverable = ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                              (self.friend_requests.as_str(), self.direct_messages.as_str(), self.presence_visible, self.discoverable,
+                               &user.username, &user.user_id, &user.createdat)).await?;
+        lwt::ensure_applied(res)
+    }
+
+    /// Whether the viewer can see the status of the user
+    pub fn shows_presence(&self, relation: Relation) -> bool {
+        match relation {
+            Relation::Myself | Relation::Friend => true,
+            Relation::BlockedBy => false,
+            Relation::FriendOfFriend | Relation::Stranger => self.presence_visible,
+        }
+    }
+
+    /// Whether the viewer may send the user a friend request
+    pub fn allows_friend_request(&self, relation: Relation) -> bool {
+        match (relation, self.friend_requests) {
+            (Relation::Myself | Relation::Friend | Relation::BlockedBy, _) => false,
+            (_, FriendRequests::Everyone) => true,
+            (Relation::FriendOfFriend, FriendRequests::FriendsOfFriends) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the viewer may send the user a direct message
+    pub fn allows_direct_message(&self, relation: Relation) -> bool {
+        match (relation, self.direct_messages) {
+            (Relation::Myself | Relation::BlockedBy, _) => false,
+            (_, DirectMessages::Everyone) => true,
+            (Relation::Friend, DirectMessages::Friends) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the user shows up in search results of the viewer
+    pub fn discoverable_by(&self, relation: Relation) -> bool {
+        match relation {
+            Relation::Myself | Relation::BlockedBy => false,
+            Relation::Friend => true,
+            Relation::FriendOfFriend | Relation::Stranger => self.discoverable,
+        }
+    }
+}
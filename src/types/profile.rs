@@ -0,0 +1,112 @@
+use scylla::frame::response::result::CqlValue;
+use serde::{Deserialize, Serialize};
+use crate::functions::validation::{normalize_desc, normalize_displayname, normalize_pronouns, validate_links};
+use crate::types::color::HexColor;
+
+pub const ALLOWED_STATUS: [u8; 4] = [0, 1, 2, 3];
+
+/// Profile columns that can be changed without re-authentication.
+///
+/// `discoverable` belongs to the privacy settings, it is still accepted here for clients
+/// written before `/users/@me/privacy` existed.
+pub const PROFILE_FIELDS: [&str; 8] = ["displayname", "status", "bannercolor", "backgroundcolor", "desc", "pronouns", "links", "discoverable"];
+
+/// Partial update of the own profile, fields that are not present stay unchanged.
+///
+/// E-mail and password are not part of it, they require re-authentication through `changeSelfInfo`.
+#[derive(Deserialize)]
+pub struct ProfilePatch {
+    pub displayname: Option<String>,
+    pub status: Option<u8>,
+    pub bannercolor: Option<String>,
+    pub backgroundcolor: Option<String>,
+    pub desc: Option<String>,
+    pub pronouns: Option<String>,
+    pub links: Option<Vec<String>>,
+    pub discoverable: Option<bool>,
+}
+
+/// Validation error of a single field, returned to the client next to the field name.
+#[derive(Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub error: String,
+}
+
+impl ProfilePatch {
+
+    /// Validates every present field and returns the columns to write.
+    ///
+    /// Nothing is returned for writing unless all fields are valid.
+    pub fn into_changes(self) -> Result<Vec<(&'static str, CqlValue)>, Vec<FieldError>> {
+        let fields = [
+            ("displayname", self.displayname),
+            ("status", self.status.map(|status| status.to_string())),
+            ("bannercolor", self.bannercolor),
+            ("backgroundcolor", self.backgroundcolor),
+            ("desc", self.desc),
+            ("pronouns", self.pronouns),
+            ("discoverable", self.discoverable.map(|discoverable| discoverable.to_string())),
+        ];
+        let mut changes = Vec::new();
+        let mut errors = Vec::new();
+
+        for (field, value) in fields {
+            let Some(value) = value else {
+                continue;
+            };
+            match validate_profile_field(field, &value) {
+                Ok(change) => changes.push(change),
+                Err(error) => errors.push(FieldError { field, error }),
+            }
+        }
+
+        if let Some(links) = self.links {
+            match validate_links(&links) {
+                Ok(links) => changes.push(("links", links_value(links))),
+                Err(error) => errors.push(FieldError { field: "links", error }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(changes)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Validates and normalizes a single profile field.
+///
+/// Every update path goes through this, so the same rules apply to `changeSelfInfo`,
+/// `setStatus` and `PATCH /users/@me`.
+///
+/// # Returns
+/// The column name together with the value to store, or a message describing why the value was rejected.
+pub fn validate_profile_field(field: &str, value: &str) -> Result<(&'static str, CqlValue), String> {
+    match field {
+        "displayname" => Ok(("displayname", CqlValue::Text(normalize_displayname(value)?))),
+        "status" => match value.parse::<u8>() {
+            Ok(status) if ALLOWED_STATUS.contains(&status) => Ok(("status", CqlValue::TinyInt(status as i8))),
+            _ => Err(String::from("Not allowed status!")),
+        },
+        "bannercolor" => Ok(("bannercolor", CqlValue::Text(value.parse::<HexColor>()?.to_string()))),
+        "backgroundcolor" => Ok(("backgroundcolor", CqlValue::Text(value.parse::<HexColor>()?.to_string()))),
+        "desc" => Ok(("desc", CqlValue::Text(normalize_desc(value)?))),
+        "pronouns" => Ok(("pronouns", CqlValue::Text(normalize_pronouns(value)?))),
+        "discoverable" => match value.parse::<bool>() {
+            Ok(discoverable) => Ok(("discoverable", CqlValue::Boolean(discoverable))),
+            Err(_) => Err(String::from("Expected true or false")),
+        },
+        // Single value updates send links separated by whitespace
+        "links" => {
+            let links: Vec<String> = value.split_whitespace().map(String::from).collect();
+            Ok(("links", links_value(validate_links(&links)?)))
+        }
+        _ => Err(String::from("Field not allowed")),
+    }
+}
+
+fn links_value(links: Vec<String>) -> CqlValue {
+    CqlValue::List(links.into_iter().map(CqlValue::Text).collect())
+}
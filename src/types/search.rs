@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use anyhow::Result;
+use futures::{StreamExt, TryStreamExt};
+use scylla::{DeserializeRow, Session};
+use uuid::Uuid;
+use crate::functions::validation::search_key;
+
+/// Longest indexed prefix in characters, longer queries are looked up by their first 16 characters.
+pub const MAX_PREFIX_LENGTH: usize = 16;
+
+// Index rows written or deleted at the same time for one user
+const MAX_CONCURRENT_WRITES: usize = 16;
+
+/// Lookup table of the user search, stored in `joltamp.user_search`
+/// partitioned by `prefix` and clustered by `user_id`.
+///
+/// Every prefix of the search key of the username, of the display name and of each word of the
+/// display name points to the user, so a search reads a single partition instead of every user.
+pub struct SearchIndex;
+
+#[derive(DeserializeRow)]
+struct NamesRow {
+    user_id: Uuid,
+    username: String,
+    displayname: String,
+    deleted: Option<bool>,
+}
+
+impl SearchIndex {
+
+    /// Returns the partition a query is looked up in
+    pub fn prefix_of(query: &str) -> String {
+        search_key(query).chars().take(MAX_PREFIX_LENGTH).collect()
+    }
+
+    /// Returns every indexed prefix of the names
+    pub fn prefixes(username: &str, displayname: &str) -> HashSet<String> {
+        let displayname = search_key(displayname);
+        let mut keys = vec![search_key(username), displayname.clone()];
+        keys.extend(displayname.split_whitespace().map(String::from));
+
+        let mut prefixes = HashSet::new();
+        for key in keys {
+            let chars: Vec<char> = key.chars().take(MAX_PREFIX_LENGTH).collect();
+            for length in 1..=chars.len() {
+                prefixes.insert(chars[..length].iter().collect());
+            }
+        }
+        prefixes
+    }
+
+    /// Adds the user under every prefix of the names
+    pub async fn index(session: &Arc<Session>, user_id: Uuid, username: &str, displayname: &str) -> Result<()> {
+        SearchIndex::insert(session, user_id, SearchIndex::prefixes(username, displayname)).await
+    }
+
+    /// Moves the user from the prefixes of the old names to the prefixes of the new names
+    pub async fn update(session: &Arc<Session>, user_id: Uuid, old: (&str, &str), new: (&str, &str)) -> Result<()> {
+        let old = SearchIndex::prefixes(old.0, old.1);
+        let new = SearchIndex::prefixes(new.0, new.1);
+        SearchIndex::delete(session, user_id, old.difference(&new).cloned().collect()).await?;
+        SearchIndex::insert(session, user_id, new.difference(&old).cloned().collect()).await
+    }
+
+    /// Removes the user from every prefix of the names
+    pub async fn remove(session: &Arc<Session>, user_id: Uuid, username: &str, displayname: &str) -> Result<()> {
+        SearchIndex::delete(session, user_id, SearchIndex::prefixes(username, displayname)).await
+    }
+
+    /// Returns at most `limit` users indexed under the prefix of the query, ordered by id and starting after `after`
+    pub async fn candidates(session: &Arc<Session>, query: &str, after: Option<Uuid>, limit: i32) -> Result<Vec<Uuid>> {
+        let prefix = SearchIndex::prefix_of(query);
+        let res = match after {
+            Some(after) => session.query_unpaged("SELECT user_id FROM joltamp.user_search WHERE prefix = ? AND user_id > ? LIMIT ?",
+                                                 (prefix, after, limit)).await?,
+            None => session.query_unpaged("SELECT user_id FROM joltamp.user_search WHERE prefix = ? LIMIT ?",
+                                          (prefix, limit)).await?,
+        };
+        let mut candidates = Vec::new();
+        for row in res.into_rows_result()?.rows::<(Uuid, )>()? {
+            candidates.push(row?.0);
+        }
+        Ok(candidates)
+    }
+
+    /// Indexes every existing user, run once at startup as `SEARCH_INDEX_MIGRATION`
+    /// so users created before the index are found.
+    ///
+    /// Index rows are idempotent, indexing a user twice changes nothing.
+    pub async fn rebuild(session: &Arc<Session>) -> Result<()> {
+        let mut rows = session.query_iter("SELECT user_id, username, displayname, deleted FROM joltamp.users", ())
+            .await?.rows_stream::<NamesRow>()?;
+        while let Some(row) = rows.try_next().await? {
+            if !row.deleted.unwrap_or(false) {
+                SearchIndex::index(session, row.user_id, &row.username, &row.displayname).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn insert(session: &Arc<Session>, user_id: Uuid, prefixes: HashSet<String>) -> Result<()> {
+        futures::stream::iter(prefixes)
+            .map(|prefix| session.query_unpaged("INSERT INTO joltamp.user_search (prefix, user_id) VALUES (?, ?)", (prefix, user_id)))
+            .buffer_unordered(MAX_CONCURRENT_WRITES)
+            .try_for_each(|_| async { Ok(()) }).await?;
+        Ok(())
+    }
+
+    async fn delete(session: &Arc<Session>, user_id: Uuid, prefixes: HashSet<String>) -> Result<()> {
+        futures::stream::iter(prefixes)
+            .map(|prefix| session.query_unpaged("DELETE FROM joltamp.user_search WHERE prefix = ? AND user_id = ?", (prefix, user_id)))
+            .buffer_unordered(MAX_CONCURRENT_WRITES)
+            .try_for_each(|_| async { Ok(()) }).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use scylla::Session;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use crate::functions::lwt;
+
+/// Maximal size of the whole serialized settings document in bytes.
+pub const MAX_SETTINGS_SIZE: usize = 16 * 1024;
+/// Maximal size of the free-form `client` section in bytes.
+pub const MAX_CLIENT_SECTION_SIZE: usize = 8 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationSettings {
+    pub desktop: bool,
+    pub sounds: bool,
+    pub mentions_only: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings { desktop: true, sounds: true, mentions_only: false }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutSettings {
+    pub compact_mode: bool,
+    pub sidebar_collapsed: bool,
+    /// Font size in percent of the default size
+    pub font_scale: u8,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        LayoutSettings { compact_mode: false, sidebar_collapsed: false, font_scale: 100 }
+    }
+}
+
+/// Settings document shared by every client of a user.
+///
+/// Known settings are typed and fall back to their defaults when missing, anything a client
+/// wants to sync on its own goes into the free-form `client` section.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientSettings {
+    pub theme: Theme,
+    pub locale: Option<String>,
+    pub notifications: NotificationSettings,
+    pub layout: LayoutSettings,
+    pub client: Map<String, Value>,
+}
+
+/// Versioned settings of a user, stored as JSON in `joltamp.user_settings` keyed by `user_id`.
+///
+/// Every write increments `version` and only succeeds if the client sent the version it
+/// based its changes on, so concurrent writes from two devices cannot overwrite each other.
+#[derive(Serialize, Clone)]
+pub struct SettingsDocument {
+    pub version: i32,
+    pub settings: ClientSettings,
+}
+
+impl ClientSettings {
+
+    /// Checks the limits of the document
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(locale) = &self.locale {
+            if locale.is_empty() || locale.len() > 35 || !locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(String::from("Invalid locale"));
+            }
+        }
+        if !(50..=200).contains(&self.layout.font_scale) {
+            return Err(String::from("Font scale must be between 50 and 200"));
+        }
+        let client_size = serde_json::to_vec(&self.client).map(|client| client.len()).unwrap_or(usize::MAX);
+        if client_size > MAX_CLIENT_SECTION_SIZE {
+            return Err(format!("Client section is too large (>{} bytes)", MAX_CLIENT_SECTION_SIZE));
+        }
+        let size = serde_json::to_vec(self).map(|settings| settings.len()).unwrap_or(usize::MAX);
+        if size > MAX_SETTINGS_SIZE {
+            return Err(format!("Settings are too large (>{} bytes)", MAX_SETTINGS_SIZE));
+        }
+        Ok(())
+    }
+}
+
+impl SettingsDocument {
+
+    /// Returns the settings of the user, version 0 with defaults if nothing was stored yet
+    pub async fn for_user(session: &Arc<Session>, user_id: Uuid) -> Result<SettingsDocument> {
+        let res = session.query_unpaged("SELECT version, settings FROM joltamp.user_settings WHERE user_id = ?",
+                                        (user_id, )).await?.into_rows_result()?;
+        match res.maybe_first_row::<(i32, String)>()? {
+            // Stored documents that no longer match the schema are reset to defaults
+            Some((version, settings)) => Ok(SettingsDocument { version, settings: serde_json::from_str(&settings).unwrap_or_default() }),
+            None => Ok(SettingsDocument { version: 0, settings: ClientSettings::default() }),
+        }
+    }
+
+    /// Replaces the settings if `expected_version` is still the stored version.
+    ///
+    /// # Returns
+    /// The new document, or `None` if another write happened in the meantime.
+    pub async fn replace(session: &Arc<Session>, user_id: Uuid, expected_version: i32, settings: ClientSettings) -> Result<Option<SettingsDocument>> {
+        let json = serde_json::to_string(&settings)?;
+        let version = expected_version.checked_add(1).ok_or_else(|| Error::msg("Invalid version"))?;
+        let res = if expected_version == 0 {
+            session.query_unpaged("INSERT INTO joltamp.user_settings (user_id, version, settings) VALUES (?, ?, ?) IF NOT EXISTS",
+                                  (user_id, version, json)).await?
+        } else {
+            session.query_unpaged("UPDATE joltamp.user_settings SET version = ?, settings = ? WHERE user_id = ? IF version = ?",
+                                  (version, json, user_id, expected_version)).await?
+        };
+        Ok(lwt::applied(res)?.then_some(SettingsDocument { version, settings }))
+    }
+
+    /// Removes the settings of the user
+    pub async fn remove(session: &Arc<Session>, user_id: Uuid) -> Result<()> {
+        session.query_unpaged("DELETE FROM joltamp.user_settings WHERE user_id = ?", (user_id, )).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::types::types::RequestError;
+
+// Largest TTL accepted by Scylla (20 years)
+const MAX_TTL_SECONDS: i64 = 630_720_000;
+
+/// Suspension of a user account, stored in `joltamp.suspensions` (one row per user).
+///
+/// Temporary suspensions are written with a TTL, so the row disappears once they expire.
+/// Suspensions ending after the maximal TTL are stored without one and lifted on lookup.
+#[derive(Serialize, DeserializeRow)]
+pub struct Suspension {
+    pub user_id: Uuid,
+    pub reason: String,
+    pub until: Option<DateTime<Utc>>,
+    pub createdat: DateTime<Utc>,
+    pub admin_id: Uuid,
+}
+
+impl Suspension {
+
+    /// Suspends the user, replacing any previous suspension
+    pub async fn create(session: &Arc<Session>, user_id: Uuid, admin_id: Uuid, reason: String, until: Option<DateTime<Utc>>) -> Result<Suspension> {
+        let suspension = Suspension { user_id, reason, until, createdat: Utc::now(), admin_id };
+        // Permanent suspensions, and ones too long for a TTL, are stored without TTL (0)
+        let ttl = until.map(|until| (until - suspension.createdat).num_seconds().max(1))
+            .filter(|ttl| *ttl <= MAX_TTL_SECONDS)
+            .unwrap_or(0);
+        session.query_unpaged(format!("INSERT INTO joltamp.suspensions (user_id, reason, until, createdat, admin_id) VALUES (?, ?, ?, ?, ?) USING TTL {}", ttl),
+                              (suspension.user_id, &suspension.reason, suspension.until, suspension.createdat, suspension.admin_id)).await?;
+        Ok(suspension)
+    }
+
+    /// Returns the active suspension of the user, lifting it if it already expired
+    pub async fn active_for(session: &Arc<Session>, user_id: Uuid) -> Result<Option<Suspension>> {
+        let res = session.query_unpaged("SELECT user_id, reason, until, createdat, admin_id FROM joltamp.suspensions WHERE user_id = ?",
+                                        (user_id, )).await?.into_rows_result()?;
+        let Some(suspension) = res.maybe_first_row::<Suspension>()? else {
+            return Ok(None);
+        };
+        if suspension.until.is_some_and(|until| until <= Utc::now()) {
+            Suspension::lift(session, user_id).await?;
+            return Ok(None);
+        }
+        Ok(Some(suspension))
+    }
+
+    /// Lifts the suspension of the user
+    pub async fn lift(session: &Arc<Session>, user_id: Uuid) -> Result<()> {
+        session.query_unpaged("DELETE FROM joltamp.suspensions WHERE user_id = ?", (user_id, )).await?;
+        Ok(())
+    }
+
+    /// Error returned to a suspended user, carrying the reason and end date
+    pub fn to_error(&self) -> RequestError {
+        #[derive(Serialize)]
+        struct Details<'a> {
+            reason: &'a str,
+            until: Option<DateTime<Utc>>,
+        }
+        RequestError::from("Account suspended")
+            .with_code("account_suspended", Details { reason: &self.reason, until: self.until })
+    }
+}
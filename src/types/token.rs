@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use anyhow::{Error, Result};
+use chrono::{DateTime, Utc};
+use scylla::{DeserializeRow, Session};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const TOKEN_COLUMNS: &str = "token_hash, token_id, user_id, name, scopes, createdat, lastused";
+
+/// Permission that can be granted to an API token.
+///
+/// User sessions (JWT) implicitly hold every scope.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    ReadProfile,
+    WriteProfile,
+    ManageFriends,
+    SendMessages,
+    /// Token, bot and credential management. Never granted to API tokens.
+    ManageAccount,
+}
+
+impl Scope {
+    pub const GRANTABLE: [Scope; 4] = [Scope::ReadProfile, Scope::WriteProfile, Scope::ManageFriends, Scope::SendMessages];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::ReadProfile => "read-profile",
+            Scope::WriteProfile => "write-profile",
+            Scope::ManageFriends => "manage-friends",
+            Scope::SendMessages => "send-messages",
+            Scope::ManageAccount => "manage-account",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Scope> {
+        match value {
+            "read-profile" => Some(Scope::ReadProfile),
+            "write-profile" => Some(Scope::WriteProfile),
+            "manage-friends" => Some(Scope::ManageFriends),
+            "send-messages" => Some(Scope::SendMessages),
+            "manage-account" => Some(Scope::ManageAccount),
+            _ => None,
+        }
+    }
+}
+
+/// Long-lived API token of a user or bot, stored in `joltamp.api_tokens`.
+///
+/// Only the SHA-256 hash of the token is stored, the plain token is returned once on creation.
+#[derive(Serialize)]
+pub struct ApiToken {
+    #[serde(skip)]
+    token_hash: String,
+    pub token_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub createdat: DateTime<Utc>,
+    pub lastused: Option<DateTime<Utc>>,
+}
+
+#[derive(DeserializeRow)]
+struct ApiTokenRow {
+    token_hash: String,
+    token_id: Uuid,
+    user_id: Uuid,
+    name: String,
+    scopes: Vec<String>,
+    createdat: DateTime<Utc>,
+    lastused: Option<DateTime<Utc>>,
+}
+
+impl From<ApiTokenRow> for ApiToken {
+    fn from(row: ApiTokenRow) -> Self {
+        ApiToken {
+            token_hash: row.token_hash,
+            token_id: row.token_id,
+            user_id: row.user_id,
+            name: row.name,
+            scopes: row.scopes.iter().filter_map(|scope| Scope::parse(scope)).collect(),
+            createdat: row.createdat,
+            lastused: row.lastused,
+        }
+    }
+}
+
+impl ApiToken {
+
+    /// Creates a new token for the user and returns it together with the plain token value
+    pub async fn create(session: &Arc<Session>, user_id: Uuid, name: String, scopes: Vec<Scope>) -> Result<(ApiToken, String)> {
+        if scopes.iter().any(|scope| !Scope::GRANTABLE.contains(scope)) {
+            return Err(Error::msg("Scope cannot be granted to a token"));
+        }
+        let plain = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let token = ApiToken {
+            token_hash: hash_token(&plain),
+            token_id: Uuid::new_v4(),
+            user_id,
+            name,
+            scopes,
+            createdat: Utc::now(),
+            lastused: None,
+        };
+        let scopes: Vec<&str> = token.scopes.iter().map(Scope::as_str).collect();
+        session.query_unpaged("INSERT INTO joltamp.api_tokens (token_hash, token_id, user_id, name, scopes, createdat) VALUES (?, ?, ?, ?, ?, ?)",
+                              (&token.token_hash, token.token_id, token.user_id, &token.name, scopes, token.createdat)).await?;
+        Ok((token, plain))
+    }
+
+    /// Looks up a token by its plain value
+    pub async fn from_plain(session: &Arc<Session>, plain: &str) -> Result<ApiToken> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.api_tokens WHERE token_hash = ?", TOKEN_COLUMNS),
+                                        (hash_token(plain), )).await?.into_rows_result()?;
+        Ok(res.first_row::<ApiTokenRow>()?.into())
+    }
+
+    /// Lists every token owned by the user
+    pub async fn list_for_user(session: &Arc<Session>, user_id: Uuid) -> Result<Vec<ApiToken>> {
+        let res = session.query_unpaged(format!("SELECT {} FROM joltamp.api_tokens WHERE user_id = ? ALLOW FILTERING", TOKEN_COLUMNS),
+                                        (user_id, )).await?.into_rows_result()?;
+        let mut tokens = Vec::new();
+        for row in res.rows::<ApiTokenRow>()? {
+            tokens.push(row?.into());
+        }
+        Ok(tokens)
+    }
+
+    /// Revokes a single token of the user
+    pub async fn revoke(session: &Arc<Session>, user_id: Uuid, token_id: Uuid) -> Result<()> {
+        let token = ApiToken::list_for_user(session, user_id).await?
+            .into_iter()
+            .find(|token| token.token_id == token_id)
+            .ok_or(Error::msg("Token not found"))?;
+        session.query_unpaged("DELETE FROM joltamp.api_tokens WHERE token_hash = ?", (&token.token_hash, )).await?;
+        Ok(())
+    }
+
+    /// Revokes every token of the user
+    pub async fn revoke_all(session: &Arc<Session>, user_id: Uuid) -> Result<()> {
+        for token in ApiToken::list_for_user(session, user_id).await? {
+            session.query_unpaged("DELETE FROM joltamp.api_tokens WHERE token_hash = ?", (&token.token_hash, )).await?;
+        }
+        Ok(())
+    }
+
+    /// Stores the current time as last usage of the token
+    pub async fn touch(&self, session: &Arc<Session>) -> Result<()> {
+        session.query_unpaged("UPDATE joltamp.api_tokens SET lastused = ? WHERE token_hash = ?",
+                              (Utc::now(), &self.token_hash)).await?;
+        Ok(())
+    }
+}
+
+fn hash_token(plain: &str) -> String {
+    Sha256::digest(plain.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
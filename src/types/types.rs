@@ -1,9 +1,24 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Custom universal error type for returning an error message via RESTful API.
 #[derive(Serialize)]
 pub struct RequestError{
     error: String,
+    /// Machine-readable error code for errors the client has to handle specifically
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[serde(flatten)]
+    details: Option<Value>,
+}
+
+impl RequestError {
+    /// Attaches a machine-readable code and additional fields (flattened into the error object)
+    pub fn with_code(mut self, code: &str, details: impl Serialize) -> Self {
+        self.code = Some(code.to_string());
+        self.details = serde_json::to_value(details).ok().filter(Value::is_object);
+        self
+    }
 }
 
 // Implementaion of From trait for ::from(String) usage
@@ -12,6 +27,21 @@ impl<T: ToString> From<T> for RequestError where std::string::String: From<T>
     fn from(value: T) -> Self {
         RequestError {
             error: String::from(value),
+            code: None,
+            details: None,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Query parameters of profile endpoints, `expand` is a comma separated list of fields
+/// whose ids should be expanded into full objects (e.g. `?expand=badges`).
+#[derive(Deserialize)]
+pub struct ProfileQuery {
+    expand: Option<String>,
+}
+
+impl ProfileQuery {
+    pub fn expands(&self, field: &str) -> bool {
+        self.expand.as_deref().is_some_and(|expand| expand.split(',').any(|value| value.trim() == field))
+    }
+}
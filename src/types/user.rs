@@ -1,15 +1,25 @@
 use std::collections::HashMap;
-use std::fmt::Debug;
 use std::sync::Arc;
-use scylla::{QueryRowsResult, Session};
+use scylla::{DeserializeRow, QueryRowsResult, Session};
+use scylla::batch::{Batch, BatchType};
+use scylla::frame::response::result::CqlValue;
 use uuid::Uuid;
 use anyhow::{Error, Result};
 use chrono::NaiveDate;
 use crate::security::passwords::{hash_password};
+use crate::security::policy::check_password;
+use crate::types::annotation::Annotation;
 use crate::types::friend::{Friend, FriendFunc};
+use crate::types::color::HexColor;
+use crate::types::privacy::PrivacySettings;
+use crate::types::profile::{validate_profile_field, PROFILE_FIELDS};
+use crate::types::search::SearchIndex;
+use crate::types::token::ApiToken;
+use crate::functions::lwt;
+use crate::types::username::{UsernameClaim, UsernameTaken, USERNAME_RESERVATION_DAYS};
 
-const ALLOWED_UPDATE_FIELDS: [&str; 7] = ["email", "password", "displayname", "status", "bannercolor", "backgroundcolor", "desc"];
-const ALLOWED_STATUS: [u8; 4] = [0, 1, 2, 3];
+const ALLOWED_UPDATE_FIELDS: [&str; 9] = ["email", "password", "displayname", "status", "bannercolor", "backgroundcolor", "desc", "pronouns", "links"];
+const USER_COLUMNS: &str = "createdat, user_id, jwt, username, email, password, displayname, friends, badges, status, bannercolor, backgroundcolor, isadmin, desc, pronouns, links, avatar, banner, isbot, owner_id, deactivated, deleted, friend_requests, direct_messages, presence_visible, discoverable";
 
 pub struct User {
     pub createdat: Option<NaiveDate>,
@@ -20,12 +30,56 @@ pub struct User {
     pub password: Option<String>,
     pub displayname: Option<String>,
     pub friends: Option<HashMap<Uuid, Friend>>,
+    /// Raw `friends` map (user id to friend status), filled by `fill_info`
+    pub relations: Option<HashMap<Uuid, i8>>,
     pub badges: Option<Vec<Uuid>>,
     pub status: Option<i8>,
-    pub bannercolor: Option<String>,
-    pub backgroundcolor: Option<String>,
+    pub bannercolor: Option<HexColor>,
+    pub backgroundcolor: Option<HexColor>,
     pub isadmin: Option<bool>,
     pub desc: Option<String>,
+    pub pronouns: Option<String>,
+    pub links: Option<Vec<String>>,
+    pub avatar: Option<String>,
+    pub banner: Option<String>,
+    pub isbot: Option<bool>,
+    pub owner_id: Option<Uuid>,
+    pub privacy: Option<PrivacySettings>,
+    /// Set while a deletion of the account is pending
+    pub deactivated: Option<bool>,
+    /// Set on the tombstone left after the account was deleted
+    pub deleted: Option<bool>,
+}
+
+// Row of joltamp.users as selected by USER_COLUMNS
+#[derive(DeserializeRow)]
+struct UserRow {
+    createdat: NaiveDate,
+    user_id: Uuid,
+    jwt: Uuid,
+    username: String,
+    email: String,
+    password: String,
+    displayname: String,
+    friends: HashMap<Uuid, i8>,
+    badges: Vec<Uuid>,
+    status: i8,
+    bannercolor: Option<String>,
+    backgroundcolor: Option<String>,
+    isadmin: Option<bool>,
+    desc: Option<String>,
+    pronouns: Option<String>,
+    links: Vec<String>,
+    avatar: Option<String>,
+    banner: Option<String>,
+    isbot: Option<bool>,
+    owner_id: Option<Uuid>,
+    deactivated: Option<bool>,
+    deleted: Option<bool>,
+    friend_requests: Option<String>,
+    direct_messages: Option<String>,
+    presence_visible: Option<bool>,
+    discoverable: Option<bool>,
 }
 
 // User implementation of functions that return user objects from accessible data
@@ -35,7 +89,14 @@ pub  trait UserFunc: std::marker::Sized{
     async fn fill_info(self, session: &Arc<Session>) -> Result<Self>;
     async fn fetch_friends(self, session: &Arc<Session>) -> Result<Self>;
     async fn update(self, session: &Arc<Session>, change_field: &str, new_value: String) -> Result<Self>;
+    async fn update_fields(self, session: &Arc<Session>, changes: Vec<(&'static str, CqlValue)>) -> Result<Self>;
+    #[allow(dead_code)]
     async fn add_friend(self, session: &Arc<Session>, friend_id: Uuid, friend_status: u8) -> Result<Self>;
+    async fn revoke_sessions(self, session: &Arc<Session>) -> Result<Self>;
+    async fn set_admin(self, session: &Arc<Session>, isadmin: bool) -> Result<Self>;
+    async fn grant_badge(self, session: &Arc<Session>, badge_id: Uuid) -> Result<Self>;
+    async fn revoke_badge(self, session: &Arc<Session>, badge_id: Uuid) -> Result<Self>;
+    async fn change_username(self, session: &Arc<Session>, new_username: String) -> Result<Self>;
 }
 impl UserFunc for User {
 
@@ -44,39 +105,49 @@ impl UserFunc for User {
         let res: QueryRowsResult;
         // Fetch data if ID is present
         if let Some(user_id) = self.user_id{
-            res = session.query_unpaged("SELECT createdat, user_id, jwt, username, email, password, displayname, friends, badges, status, bannercolor, backgroundcolor, isadmin, desc FROM joltamp.users WHERE user_id = ? ALLOW FILTERING",
+            res = session.query_unpaged(format!("SELECT {} FROM joltamp.users WHERE user_id = ? ALLOW FILTERING", USER_COLUMNS),
                                         (&user_id, )).await?.into_rows_result()?;
         }
         // Fetch data if JWT is present
         else if let Some(jwt) = self.jwt{
-            res = session.query_unpaged("SELECT createdat, user_id, jwt, username, email, password, displayname, friends, badges, status, bannercolor, backgroundcolor, isadmin, desc FROM joltamp.users WHERE jwt = ? ALLOW FILTERING",
+            res = session.query_unpaged(format!("SELECT {} FROM joltamp.users WHERE jwt = ? ALLOW FILTERING", USER_COLUMNS),
                                         (&jwt, )).await?.into_rows_result()?;
         }
         // Fetch data if Email is present
         else if let Some(email) = self.email{
-            res = session.query_unpaged("SELECT createdat, user_id, jwt, username, email, password, displayname, friends, badges, status, bannercolor, backgroundcolor, isadmin, desc FROM joltamp.users WHERE email = ? ALLOW FILTERING",
+            res = session.query_unpaged(format!("SELECT {} FROM joltamp.users WHERE email = ? ALLOW FILTERING", USER_COLUMNS),
                                         (&email, )).await?.into_rows_result()?;
         }
         // Return error if no data is provided
         else {
             return Err(Error::msg("Invalid"));
         }
-        let (createdat, user_id, jwt, username, email, password, displayname, raw_friends, badges, status, bannercolor, backgroundcolor, isadmin, desc)
-            = res.first_row::<(NaiveDate, Uuid, Uuid, String, String, String, String, HashMap<Uuid, i8>, Vec<Uuid>, i8, Option<String>, Option<String>, Option<bool>, Option<String>)>()?;
+        let row = res.first_row::<UserRow>()?;
 
-        self.createdat = Some(createdat);
-        self.user_id = Some(user_id);
-        self.jwt = Some(jwt);
-        self.username = Some(username);
-        self.email = Some(email);
-        self.password = Some(password);
-        self.displayname = Some(displayname);
-        self.badges = Some(badges);
-        self.status = Some(status);
-        self.bannercolor = bannercolor;
-        self.backgroundcolor = backgroundcolor;
-        self.isadmin = isadmin;
-        self.desc = desc;
+        self.createdat = Some(row.createdat);
+        self.user_id = Some(row.user_id);
+        self.jwt = Some(row.jwt);
+        self.username = Some(row.username);
+        self.email = Some(row.email);
+        self.password = Some(row.password);
+        self.displayname = Some(row.displayname);
+        self.badges = Some(row.badges);
+        self.status = Some(row.status);
+        // Colors stored before validation existed may not parse, they are treated as unset
+        self.bannercolor = row.bannercolor.and_then(|color| color.parse().ok());
+        self.backgroundcolor = row.backgroundcolor.and_then(|color| color.parse().ok());
+        self.isadmin = row.isadmin;
+        self.desc = row.desc;
+        self.pronouns = row.pronouns;
+        self.links = Some(row.links);
+        self.avatar = row.avatar;
+        self.banner = row.banner;
+        self.isbot = row.isbot;
+        self.owner_id = row.owner_id;
+        self.relations = Some(row.friends);
+        self.privacy = Some(PrivacySettings::from_columns(row.friend_requests, row.direct_messages, row.presence_visible, row.discoverable));
+        self.deactivated = row.deactivated;
+        self.deleted = row.deleted;
 
         Ok(self)
     }
@@ -86,18 +157,20 @@ impl UserFunc for User {
                               (&self.createdat, &self.user_id, &self.username)).await;
 
         if let Err(err) = &res{
-            println!("{:?}", err);
+            tracing::warn!("Cannot fetch friends: {:?}", err);
         }
 
         if let Ok(friends) = res {
             let (friends, ) = friends.into_rows_result()?.first_row::<(HashMap<Uuid, i8>, )>()?;
-            let mut returnFriends: HashMap<Uuid, Friend> = HashMap::new();
+            let mut annotations = Annotation::for_owner(session, self.user_id.unwrap_or(Uuid::nil())).await?;
+            let mut return_friends: HashMap<Uuid, Friend> = HashMap::new();
             for friend in friends {
-                let friend = Friend::from_uuid(friend.0, friend.1).fill_info(&session).await.unwrap();
-                returnFriends.insert(friend.user_id.clone(), friend);
+                let mut friend = Friend::from_uuid(friend.0, friend.1).fill_info(session).await?;
+                friend.nickname = annotations.remove(&friend.user_id).and_then(|annotation| annotation.nickname);
+                return_friends.insert(friend.user_id, friend);
             }
 
-            self.friends = Some(returnFriends);
+            self.friends = Some(return_friends);
 
         }else{
             return Err(Error::msg("Cannot fetch friends"));
@@ -107,126 +180,281 @@ impl UserFunc for User {
     }
 
     async fn update(mut self, session: &Arc<Session>, change_field: &str, mut new_value: String) -> Result<Self> {
-        if let None = self.jwt {
+        if self.jwt.is_none() {
             return Err(Error::msg("JWT is not set"));
         }
+        self.validate_update(change_field, &new_value)?;
 
-        println!("{}, {}", change_field, new_value);
+        // Profile fields share their validation with the PATCH endpoint
+        if PROFILE_FIELDS.contains(&change_field) {
+            let change = validate_profile_field(change_field, &new_value).map_err(Error::msg)?;
+            return self.update_fields(session, vec![change]).await;
+        }
 
-        if ALLOWED_UPDATE_FIELDS.contains(&change_field) {
-            if change_field == "password"{
-                new_value = hash_password(&mut new_value).unwrap();
-            }
-            if change_field == "status" && !ALLOWED_STATUS.contains(&new_value.parse::<u8>()?) {
-                return Err(Error::msg("Not allowed status!"));
-            }
-            if change_field == "email" && (!new_value.contains("@") || new_value.len() < 3){
-                return Err(Error::msg("Invalid email"));
-            }
-            let res = session.query_unpaged(format!("UPDATE joltamp.users SET {} = ? WHERE username = ? AND user_id = ? AND createdat = ?", &change_field),
-                                            (&new_value, &self.username, &self.user_id, &self.createdat)).await;
-            if let Ok(_) = res {
-                match change_field {
-                    "email" => self.email = Some(new_value.to_string()),
-                    "password" => self.password = Some(new_value.to_string()),
-                    "displayname" => self.displayname = Some(new_value.to_string()),
-                    "status" => self.status = Some(new_value.parse::<i8>()?),
-                    "bannercolor" => self.bannercolor = Some(new_value.to_string()),
-                    "backgroundcolor" => self.backgroundcolor = Some(new_value.to_string()),
-                    _ => {
-                        return Err(Error::msg("Action not allowed"));
-                    }
+        if change_field == "password"{
+            new_value = hash_password(&mut new_value).unwrap();
+        }
+        let res = session.query_unpaged(format!("UPDATE joltamp.users SET {} = ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS", &change_field),
+                                        (&new_value, &self.username, &self.user_id, &self.createdat)).await;
+        if res.map_err(Error::from).and_then(lwt::ensure_applied).is_ok() {
+            match change_field {
+                "email" => self.email = Some(new_value.to_string()),
+                "password" => self.password = Some(new_value.to_string()),
+                _ => {
+                    return Err(Error::msg("Action not allowed"));
                 }
-                Ok(self)
-            }else{
-                return Err(Error::msg("Update failed"));
             }
+            Ok(self)
         }else{
-            Err(Error::msg("Field not allowed"))
+            Err(Error::msg("Update failed"))
         }
     }
+    /// Writes already validated columns in a single statement, so either all or none are applied
+    async fn update_fields(mut self, session: &Arc<Session>, changes: Vec<(&'static str, CqlValue)>) -> Result<Self> {
+        if changes.is_empty() {
+            return Ok(self);
+        }
+        let columns: Vec<String> = changes.iter().map(|(column, _)| format!("{} = ?", column)).collect();
+        let mut values: Vec<CqlValue> = changes.iter().map(|(_, value)| value.clone()).collect();
+        values.push(CqlValue::Text(self.username.clone().unwrap_or_default()));
+        values.push(CqlValue::Uuid(self.user_id.unwrap_or(Uuid::nil())));
+        values.push(CqlValue::Date(self.createdat.unwrap_or(NaiveDate::MIN).into()));
+        session.query_unpaged(format!("UPDATE joltamp.users SET {} WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS", columns.join(", ")), values)
+            .await.map_err(Error::from).and_then(lwt::ensure_applied).map_err(|_| Error::msg("Update failed"))?;
+
+        let old_displayname = self.displayname.clone().unwrap_or_default();
+        for (column, value) in changes {
+            match (column, value) {
+                ("displayname", CqlValue::Text(value)) => self.displayname = Some(value),
+                ("status", CqlValue::TinyInt(value)) => self.status = Some(value),
+                ("bannercolor", CqlValue::Text(value)) => self.bannercolor = value.parse().ok(),
+                ("backgroundcolor", CqlValue::Text(value)) => self.backgroundcolor = value.parse().ok(),
+                ("desc", CqlValue::Text(value)) => self.desc = Some(value),
+                ("pronouns", CqlValue::Text(value)) => self.pronouns = Some(value),
+                ("avatar", CqlValue::Text(value)) => self.avatar = Some(value),
+                ("banner", CqlValue::Text(value)) => self.banner = Some(value),
+                ("links", CqlValue::List(values)) => self.links = Some(values.into_iter().filter_map(|value| value.into_string()).collect()),
+                // Same column as the privacy setting
+                ("discoverable", CqlValue::Boolean(value)) => self.privacy.get_or_insert_with(PrivacySettings::default).discoverable = value,
+                _ => {}
+            }
+        }
+        let (username, displayname) = (self.username.as_deref().unwrap_or(""), self.displayname.as_deref().unwrap_or(""));
+        if displayname != old_displayname {
+            // The row is already updated, a stale index only affects search results
+            if let Err(err) = SearchIndex::update(session, self.user_id.unwrap_or(Uuid::nil()), (username, &old_displayname), (username, displayname)).await {
+                tracing::warn!("Cannot update search index of {:?}: {:?}", self.user_id, err);
+            }
+        }
+        Ok(self)
+    }
     async fn add_friend(mut self, session: &Arc<Session>, friend_id: Uuid, friend_status: u8) -> Result<Self> {
 
-        if let None = self.friends {
+        if self.friends.is_none() {
             self = self.fetch_friends(session).await?;
         }
         
         if self.friends.as_ref().unwrap().contains_key(&friend_id) {
-            let res = session.query_unpaged("UPDATE joltamp.users SET friends = friends + {?: ?} WHERE user_id = ? AND createdat = ? AND username = ?",
+            let res = session.query_unpaged("UPDATE joltamp.users SET friends = friends + {?: ?} WHERE user_id = ? AND createdat = ? AND username = ? IF EXISTS",
                                             (friend_id, 2, &self.user_id, &self.createdat, &self.username)).await;
 
-            if let Err(_) = res{
+            if res.map_err(Error::from).and_then(lwt::ensure_applied).is_err(){
                 return Err(Error::msg("Cannot update user friends"));
             }
         }else {
-            let res = session.query_unpaged("UPDATE joltamp.users SET friends = friends + {?: ?} WHERE user_id = ? AND createdat = ? AND username = ?",
+            let res = session.query_unpaged("UPDATE joltamp.users SET friends = friends + {?: ?} WHERE user_id = ? AND createdat = ? AND username = ? IF EXISTS",
                                             (friend_id, friend_status as i8, &self.user_id, &self.createdat, &self.username)).await;
 
-            if let Err(_) = res{
+            if res.map_err(Error::from).and_then(lwt::ensure_applied).is_err(){
                 return Err(Error::msg("Cannot update user friends"));
             }
         }
         Ok(self)
     }
+
+    /// Invalidates the user session by rotating the JWT and revokes every API token
+    async fn revoke_sessions(mut self, session: &Arc<Session>) -> Result<Self> {
+        let jwt = Uuid::new_v4();
+        session.query_unpaged("UPDATE joltamp.users SET jwt = ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                              (jwt, &self.username, &self.user_id, &self.createdat)).await
+            .map_err(Error::from).and_then(lwt::ensure_applied).map_err(|_| Error::msg("Cannot revoke sessions"))?;
+        ApiToken::revoke_all(session, self.user_id.unwrap_or(Uuid::nil())).await?;
+        self.jwt = Some(jwt);
+        Ok(self)
+    }
+    async fn set_admin(mut self, session: &Arc<Session>, isadmin: bool) -> Result<Self> {
+        session.query_unpaged("UPDATE joltamp.users SET isadmin = ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                              (isadmin, &self.username, &self.user_id, &self.createdat)).await
+            .map_err(Error::from).and_then(lwt::ensure_applied).map_err(|_| Error::msg("Update failed"))?;
+        self.isadmin = Some(isadmin);
+        Ok(self)
+    }
+    async fn grant_badge(mut self, session: &Arc<Session>, badge_id: Uuid) -> Result<Self> {
+        let mut badges = self.badges.take().unwrap_or_default();
+        if badges.contains(&badge_id) {
+            return Err(Error::msg("Badge already granted"));
+        }
+        session.query_unpaged("UPDATE joltamp.users SET badges = badges + ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                              (vec![badge_id], &self.username, &self.user_id, &self.createdat)).await
+            .map_err(Error::from).and_then(lwt::ensure_applied).map_err(|_| Error::msg("Update failed"))?;
+        badges.push(badge_id);
+        self.badges = Some(badges);
+        Ok(self)
+    }
+    async fn revoke_badge(mut self, session: &Arc<Session>, badge_id: Uuid) -> Result<Self> {
+        let mut badges = self.badges.take().unwrap_or_default();
+        if !badges.contains(&badge_id) {
+            return Err(Error::msg("Badge not granted"));
+        }
+        session.query_unpaged("UPDATE joltamp.users SET badges = badges - ? WHERE username = ? AND user_id = ? AND createdat = ? IF EXISTS",
+                              (vec![badge_id], &self.username, &self.user_id, &self.createdat)).await
+            .map_err(Error::from).and_then(lwt::ensure_applied).map_err(|_| Error::msg("Update failed"))?;
+        badges.retain(|badge| *badge != badge_id);
+        self.badges = Some(badges);
+        Ok(self)
+    }
+
+    /// Moves the user row to a new username.
+    ///
+    /// The username is part of the primary key, so the row is copied as JSON (keeping every column)
+    /// and the old one deleted. The history entry and the reservation of the old name are written
+    /// in the same logged batch, so either the whole change is applied or none of it.
+    ///
+    /// The new name is claimed in `joltamp.usernames` first, a `UsernameTaken` error is returned
+    /// if another user holds it. Other updates of the row use `IF EXISTS`, so they fail instead of
+    /// recreating the old row while it is moved.
+    async fn change_username(mut self, session: &Arc<Session>, new_username: String) -> Result<Self> {
+        let old_username = self.username.clone().ok_or(Error::msg("Username is not set"))?;
+        let user_id = self.user_id.unwrap_or(Uuid::nil());
+        if !UsernameClaim::claim(session, &new_username, user_id).await? {
+            return Err(Error::new(UsernameTaken));
+        }
+        match self.move_row(session, &old_username, &new_username).await {
+            Ok(()) => {
+                if let Err(err) = UsernameClaim::release(session, &old_username, user_id).await {
+                    tracing::warn!("Cannot release username of {:?}: {:?}", self.user_id, err);
+                }
+            }
+            Err(err) => {
+                let _ = UsernameClaim::release(session, &new_username, user_id).await;
+                return Err(err);
+            }
+        }
+
+        let displayname = self.displayname.as_deref().unwrap_or("");
+        if let Err(err) = SearchIndex::update(session, user_id, (&old_username, displayname), (&new_username, displayname)).await {
+            tracing::warn!("Cannot update search index of {:?}: {:?}", self.user_id, err);
+        }
+        self.username = Some(new_username);
+        Ok(self)
+    }
 }
 impl User {
 
-    /// Creates user object from user id
-    pub fn from_user_id(user_id: Uuid) -> User {
+    // Copies the row to the new username and deletes the old one, see `change_username`
+    async fn move_row(&self, session: &Arc<Session>, old_username: &str, new_username: &str) -> Result<()> {
+        let res = session.query_unpaged("SELECT JSON * FROM joltamp.users WHERE username = ? AND user_id = ? AND createdat = ?",
+                                        (&old_username, &self.user_id, &self.createdat)).await?.into_rows_result()?;
+        let (row, ) = res.first_row::<(String, )>()?;
+        let mut row: serde_json::Value = serde_json::from_str(&row)?;
+        row["username"] = serde_json::Value::String(new_username.to_string());
+
+        let mut batch = Batch::new(BatchType::Logged);
+        batch.append_statement("INSERT INTO joltamp.users JSON ? DEFAULT UNSET");
+        batch.append_statement("DELETE FROM joltamp.users WHERE username = ? AND user_id = ? AND createdat = ?");
+        batch.append_statement("INSERT INTO joltamp.username_history (user_id, changedat, old_username, new_username) VALUES (?, ?, ?, ?)");
+        batch.append_statement(format!("INSERT INTO joltamp.reserved_usernames (username, user_id) VALUES (?, ?) USING TTL {}", USERNAME_RESERVATION_DAYS * 24 * 60 * 60).as_str());
+        // Reclaiming an own reserved name ends its reservation
+        batch.append_statement("DELETE FROM joltamp.reserved_usernames WHERE username = ?");
+        session.batch(&batch, (
+            (row.to_string(), ),
+            (&old_username, &self.user_id, &self.createdat),
+            (&self.user_id, chrono::Utc::now(), &old_username, &new_username),
+            (&old_username, &self.user_id),
+            (&new_username, ),
+        )).await.map_err(|_| Error::msg("Username change failed"))?;
+        Ok(())
+    }
+
+    /// Returns the ids of the bots owned by the user
+    pub async fn owned_bots(session: &Arc<Session>, owner_id: Uuid) -> Result<Vec<Uuid>> {
+        let res = session.query_unpaged("SELECT user_id FROM joltamp.users WHERE owner_id = ? ALLOW FILTERING", (owner_id, ))
+            .await?.into_rows_result()?;
+        let mut bots = Vec::new();
+        for row in res.rows::<(Uuid, )>()? {
+            bots.push(row?.0);
+        }
+        Ok(bots)
+    }
+
+    /// Validates a value for `update` without writing it, so callers can check it before spending a re-auth token.
+    ///
+    /// Password policy violations are returned as `PasswordPolicyError`.
+    pub fn validate_update(&self, change_field: &str, new_value: &str) -> Result<()> {
+        if !ALLOWED_UPDATE_FIELDS.contains(&change_field) {
+            return Err(Error::msg("Field not allowed"));
+        }
+        if PROFILE_FIELDS.contains(&change_field) {
+            return validate_profile_field(change_field, new_value).map(|_| ()).map_err(Error::msg);
+        }
+        match change_field {
+            "password" => check_password(new_value, self.username.as_deref().unwrap_or(""), self.email.as_deref().unwrap_or(""))
+                .map_err(Error::new),
+            "email" if !new_value.contains('@') || new_value.len() < 3 => Err(Error::msg("Invalid email")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates user object with no data filled
+    fn empty() -> User {
         User {
             createdat: None,
-            user_id: Some(user_id),
+            user_id: None,
             jwt: None,
             username: None,
             email: None,
             password: None,
             displayname: None,
             friends: None,
+            relations: None,
             badges: None,
             status: None,
             bannercolor: None,
             backgroundcolor: None,
             isadmin: None,
             desc: None,
+            pronouns: None,
+            links: None,
+            avatar: None,
+            banner: None,
+            isbot: None,
+            owner_id: None,
+            privacy: None,
+            deactivated: None,
+            deleted: None,
+        }
+    }
+
+    /// Creates user object from user id
+    pub fn from_user_id(user_id: Uuid) -> User {
+        User {
+            user_id: Some(user_id),
+            ..User::empty()
         }
     }
     /// Creates user object from user jwt
     pub fn from_user_jwt(jwt: Uuid) -> User {
         User {
-            createdat: None,
-            user_id: None,
             jwt: Some(jwt),
-            username: None,
-            email: None,
-            password: None,
-            displayname: None,
-            friends: None,
-            badges: None,
-            status: None,
-            bannercolor: None,
-            backgroundcolor: None,
-            isadmin: None,
-            desc: None,
+            ..User::empty()
         }
     }
 
     /// Creates user object from user email
     pub fn from_user_email(email: String) -> User {
         User {
-            createdat: None,
-            user_id: None,
-            jwt: None,
-            username: None,
             email: Some(email),
-            password: None,
-            displayname: None,
-            friends: None,
-            badges: None,
-            status: None,
-            bannercolor: None,
-            backgroundcolor: None,
-            isadmin: None,
-            desc: None,
+            ..User::empty()
         }
     }
-}
\ No newline at end of file
+}
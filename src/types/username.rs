@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use scylla::{DeserializeRow, Session};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::functions::lwt;
+
+/// Minimal time between two username changes of the same user.
+pub const USERNAME_COOLDOWN_DAYS: i64 = 30;
+
+/// How long a previous username stays reserved for its former owner.
+///
+/// Reservations live in `joltamp.reserved_usernames` (keyed by `username`) and are written
+/// with a TTL, so the name becomes free again on its own once the grace period is over.
+pub const USERNAME_RESERVATION_DAYS: i64 = 90;
+
+/// Username change of a user, stored in `joltamp.username_history`
+/// partitioned by `user_id` and clustered by `changedat`.
+#[derive(Serialize, DeserializeRow)]
+pub struct UsernameChange {
+    pub user_id: Uuid,
+    pub changedat: DateTime<Utc>,
+    pub old_username: String,
+    pub new_username: String,
+}
+
+impl UsernameChange {
+
+    /// Lists every username change of the user, newest first
+    pub async fn list_for_user(session: &Arc<Session>, user_id: Uuid) -> Result<Vec<UsernameChange>> {
+        let res = session.query_unpaged("SELECT user_id, changedat, old_username, new_username FROM joltamp.username_history WHERE user_id = ?",
+                                        (user_id, )).await?.into_rows_result()?;
+        let mut changes = Vec::new();
+        for row in res.rows::<UsernameChange>()? {
+            changes.push(row?);
+        }
+        changes.sort_by_key(|change| std::cmp::Reverse(change.changedat));
+        Ok(changes)
+    }
+
+    /// Returns the time from which the user may change the username again, if still in cooldown
+    pub async fn cooldown_until(session: &Arc<Session>, user_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        let last = UsernameChange::list_for_user(session, user_id).await?.into_iter().next();
+        Ok(last.map(|change| change.changedat + Duration::days(USERNAME_COOLDOWN_DAYS))
+            .filter(|until| *until > Utc::now()))
+    }
+
+    /// Returns the former owner of a username reserved after a change
+    pub async fn reserved_by(session: &Arc<Session>, username: &str) -> Result<Option<Uuid>> {
+        let res = session.query_unpaged("SELECT user_id FROM joltamp.reserved_usernames WHERE username = ?",
+                                        (username, )).await?.into_rows_result()?;
+        Ok(res.maybe_first_row::<(Uuid, )>()?.map(|(user_id, )| user_id))
+    }
+}
+
+/// Returned when a username is claimed by another user.
+#[derive(Debug)]
+pub struct UsernameTaken;
+
+impl Display for UsernameTaken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Username already used")
+    }
+}
+
+impl std::error::Error for UsernameTaken {}
+
+/// Current username of every user, stored in `joltamp.usernames` keyed by `username`.
+///
+/// A name is claimed with a lightweight transaction before it is written to `joltamp.users`,
+/// so two users can never end up with the same username.
+pub struct UsernameClaim;
+
+#[derive(DeserializeRow)]
+struct ClaimRow {
+    user_id: Uuid,
+    username: String,
+    deleted: Option<bool>,
+}
+
+impl UsernameClaim {
+
+    /// Claims the username for the user.
+    ///
+    /// # Returns
+    /// `false` if another user holds the username, claiming an own username again succeeds.
+    pub async fn claim(session: &Arc<Session>, username: &str, user_id: Uuid) -> Result<bool> {
+        let res = session.query_unpaged("INSERT INTO joltamp.usernames (username, user_id) VALUES (?, ?) IF NOT EXISTS",
+                                        (username, user_id)).await?;
+        if lwt::applied(res)? {
+            return Ok(true);
+        }
+        let res = session.query_unpaged("SELECT user_id FROM joltamp.usernames WHERE username = ?", (username, ))
+            .await?.into_rows_result()?;
+        Ok(res.maybe_first_row::<(Uuid, )>()?.is_some_and(|(holder, )| holder == user_id))
+    }
+
+    /// Releases the username, unless another user claimed it in the meantime
+    pub async fn release(session: &Arc<Session>, username: &str, user_id: Uuid) -> Result<()> {
+        session.query_unpaged("DELETE FROM joltamp.usernames WHERE username = ? IF user_id = ?", (username, user_id)).await?;
+        Ok(())
+    }
+
+    /// Claims the username of every existing user, run once at startup as `USERNAMES_MIGRATION`.
+    ///
+    /// If older rows share a username, the first one found keeps the claim.
+    pub async fn backfill(session: &Arc<Session>) -> Result<()> {
+        let mut rows = session.query_iter("SELECT user_id, username, deleted FROM joltamp.users", ())
+            .await?.rows_stream::<ClaimRow>()?;
+        while let Some(row) = rows.try_next().await? {
+            if !row.deleted.unwrap_or(false) {
+                UsernameClaim::claim(session, &row.username, row.user_id).await?;
+            }
+        }
+        Ok(())
+    }
+}